@@ -38,6 +38,30 @@ impl Deref for OtherUrl {
     }
 }
 
+/// A day in the `date` format, e.g. `2023-01-31`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Date(String);
+
+impl Deref for Date {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A point in time in the `date-time` format, e.g. `2023-01-31T18:00:00+01:00`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DateTime(String);
+
+impl Deref for DateTime {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 """
 
 
@@ -49,6 +73,10 @@ def add_field(fp: typing.TextIO, description: Dict[str, Any]) -> str:
                     return f"OparlUrl<{ref}>"
                 else:
                     return f"OtherUrl"
+            elif description.get("format") == "date":
+                return "Date"
+            elif description.get("format") == "date-time":
+                return "DateTime"
             else:
                 return f"String"
         case "array":
@@ -105,6 +133,30 @@ def write_struct(
     fp.write("        self.id.as_ref().map(|x| x.as_str())\n")
     fp.write("    }\n")
     fp.write("\n")
+    fp.write("    fn get_type(&self) -> Option<&str> {\n")
+    fp.write("        self.r#type.as_deref()\n")
+    fp.write("    }\n")
+    fp.write("\n")
+    fp.write("    fn field_names() -> &'static [&'static str] {\n")
+    fp.write('        &["' + '", "'.join(key for key, _, _ in rust_fields) + '"]\n')
+    fp.write("    }\n")
+    fp.write("\n")
+    fp.write("    fn get_other(&self) -> &HashMap<String, Value> {\n")
+    fp.write("        &self.other\n")
+    fp.write("    }\n")
+    fp.write("\n")
+    keys = [key for key, _, _ in rust_fields]
+    for key in ["created", "modified"]:
+        if key in keys:
+            fp.write(f"    fn get_{key}(&self) -> Option<&str> {{\n")
+            fp.write(f"        self.{key}.as_deref().map(|x| x.as_str())\n")
+            fp.write("    }\n")
+            fp.write("\n")
+    if "deleted" in keys:
+        fp.write("    fn get_deleted(&self) -> bool {\n")
+        fp.write("        self.deleted.unwrap_or(false)\n")
+        fp.write("    }\n")
+        fp.write("\n")
     fp.write("}\n\n")
 
 
@@ -115,7 +115,8 @@ function add_handler() {
         ev.preventDefault();
         let url = document.getElementById("city-url").value;
         console.log("Running city " + url);
-        await test_city(url, ui.channel);
+        // The messages follow the language of the page
+        await test_city(url, document.documentElement.lang, ui.channel);
     });
     // noinspection JSIgnoredPromiseFromCall
     init_endpoint_list();
@@ -0,0 +1,197 @@
+//! A cache in a single zstd-compressed tar archive, e.g. to keep or share the responses of a run
+//! or as test data
+//!
+//! The archive contains a cache directory, either with the manifest of [FileCache] or in the
+//! layout of older versions. We read the whole archive into memory and keep new responses there
+//! until [ArchiveCache::write].
+//!
+//! [FileCache]: crate::client::FileCache
+
+use crate::checkpoint::CHECKPOINT_FILE;
+use crate::client::{
+    legacy_key, read_manifest, Cache, ManifestEntry, ResponseMetadata, MANIFEST_FILE,
+};
+use crate::incremental::INDEX_FILE;
+use anyhow::{Context, Result};
+use fs_err::File;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+use tar::{Archive, Builder, Header};
+use zstd::{Decoder, Encoder};
+
+#[derive(Default)]
+struct ArchiveFiles {
+    /// The files of the cache directory by their relative path, without the manifest
+    files: BTreeMap<String, Vec<u8>>,
+    manifest: HashMap<String, ManifestEntry>,
+    /// Whether something was added since we read the archive
+    changed: bool,
+}
+
+pub struct ArchiveCache {
+    path: PathBuf,
+    contents: Mutex<ArchiveFiles>,
+    legacy_prefix: Option<String>,
+}
+
+impl ArchiveCache {
+    /// Reads the archive, or starts an empty one if the file doesn't exist yet
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut contents = ArchiveFiles::default();
+        if path.is_file() {
+            let mut archive = Archive::new(Decoder::new(File::open(path)?)?);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().to_string();
+                let name = name.trim_start_matches("./").to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                contents.files.insert(name, data);
+            }
+            if let Some(manifest) = contents.files.remove(MANIFEST_FILE) {
+                contents.manifest = read_manifest(manifest.as_slice())?;
+            }
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            contents: Mutex::new(contents),
+            legacy_prefix: None,
+        })
+    }
+
+    /// Also reads the responses cached by older versions, see [crate::client::FileCache]
+    pub fn with_legacy_prefix(self, prefix: String) -> Self {
+        Self {
+            legacy_prefix: Some(prefix),
+            ..self
+        }
+    }
+
+    /// All stored responses, sorted by url
+    pub fn entries(&self) -> Vec<ManifestEntry> {
+        let mut entries: Vec<_> = self.contents.lock().manifest.values().cloned().collect();
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        entries
+    }
+
+    /// Writes the archive if responses were added since we read it
+    pub fn write(&self) -> Result<()> {
+        let mut contents = self.contents.lock();
+        if !contents.changed {
+            return Ok(());
+        }
+        let mut manifest: Vec<_> = contents.manifest.values().collect();
+        manifest.sort_by(|a, b| a.url.cmp(&b.url));
+        let mut manifest_lines = Vec::new();
+        for entry in manifest {
+            serde_json::to_writer(&mut manifest_lines, entry)?;
+            manifest_lines.push(b'\n');
+        }
+
+        // Like the checkpoint, write to a temporary file first so a crash keeps the old archive
+        let temp_path = self.path.with_extension("tmp");
+        let encoder = Encoder::new(BufWriter::new(File::create(&temp_path)?), 0)?;
+        let mut builder = Builder::new(encoder);
+        let files = contents
+            .files
+            .iter()
+            .map(|(name, data)| (name.as_str(), data.as_slice()));
+        for (name, data) in files.chain([(MANIFEST_FILE, manifest_lines.as_slice())]) {
+            let mut header = Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data)?;
+        }
+        builder.into_inner()?.finish()?;
+        fs_err::rename(temp_path, &self.path)?;
+        contents.changed = false;
+        Ok(())
+    }
+
+    /// Packs a cache directory into an archive, returning the number of files
+    ///
+    /// The checkpoint and the index of `--incremental` belong to a run in the cache directory
+    /// and temporary files are from writes that didn't finish, so they're left out
+    pub fn pack(cache_dir: &Path, archive: &Path) -> Result<usize> {
+        let encoder = Encoder::new(BufWriter::new(File::create(archive)?), 0)?;
+        let mut builder = Builder::new(encoder);
+        let mut count = 0;
+        let mut dirs = vec![cache_dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = fs_err::read_dir(&dir)?.collect::<Result<Vec<_>, _>>()?;
+            entries.sort_by_key(|entry| entry.path());
+            for entry in entries {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let name = path
+                    .strip_prefix(cache_dir)
+                    .context("The file is not in the cache directory")?;
+                let run_state = [CHECKPOINT_FILE, INDEX_FILE].map(Path::new).contains(&name);
+                let temporary = name.extension().is_some_and(|extension| extension == "tmp");
+                if run_state || temporary {
+                    continue;
+                }
+                builder.append_path_with_name(&path, name)?;
+                count += 1;
+            }
+        }
+        builder.into_inner()?.finish()?;
+        Ok(count)
+    }
+}
+
+impl Cache for ArchiveCache {
+    fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.contents.lock().files.get(key).cloned())
+    }
+
+    fn set_inner(&self, key: &str, data: &[u8]) -> Result<()> {
+        let mut contents = self.contents.lock();
+        contents.files.insert(key.to_string(), data.to_vec());
+        contents.changed = true;
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        let contents = self.contents.lock();
+        let key = match contents.manifest.get(url) {
+            Some(entry) => entry.file.clone(),
+            None => match legacy_key(self.legacy_prefix.as_deref(), url) {
+                Some(key) => key,
+                None => return Ok(None),
+            },
+        };
+        Ok(contents.files.get(&key).cloned())
+    }
+
+    fn set(&self, url: &str, data: &[u8]) -> Result<()> {
+        self.set_response(url, data, &ResponseMetadata::default())
+    }
+
+    fn get_metadata(&self, url: &str) -> Result<Option<ResponseMetadata>> {
+        Ok(self
+            .contents
+            .lock()
+            .manifest
+            .get(url)
+            .map(|entry| entry.metadata.clone()))
+    }
+
+    fn set_response(&self, url: &str, data: &[u8], metadata: &ResponseMetadata) -> Result<()> {
+        let entry = ManifestEntry::new(url, metadata);
+        let mut contents = self.contents.lock();
+        contents.files.insert(entry.file.clone(), data.to_vec());
+        contents.manifest.insert(entry.url.clone(), entry);
+        contents.changed = true;
+        Ok(())
+    }
+}
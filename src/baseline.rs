@@ -0,0 +1,84 @@
+//! Known problems from an earlier run, so that new problems stand out in the report
+//!
+//! The baseline only stores kind, object type, path and object id of each problem. The details
+//! contain counts and example urls that change between runs, so they only serve as key for
+//! problems that have neither an object id nor an url.
+
+use crate::storage::Storage;
+use crate::visit::Problem;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub kind: String,
+    /// Distinguishes e.g. a missing meeting from a missing person
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    pub path: String,
+    /// The object id, or the url or detail for problems without an object
+    pub id: Option<String>,
+}
+
+impl From<&Problem> for BaselineEntry {
+    fn from(problem: &Problem) -> Self {
+        let id = problem
+            .id
+            .clone()
+            .or_else(|| problem.url.clone())
+            .or_else(|| (!problem.detail.is_empty()).then(|| problem.detail.clone()));
+        Self {
+            kind: problem.kind.name().to_string(),
+            type_name: problem.kind.type_name().map(ToString::to_string),
+            path: problem.path.clone(),
+            id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Baseline {
+    /// Sorted so that the file has a stable order and can be checked into git
+    entries: BTreeSet<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Takes all problems of a finished run
+    pub fn from_storage(storage: &Storage) -> Self {
+        Self {
+            entries: storage
+                .problems
+                .lock()
+                .iter()
+                .map(BaselineEntry::from)
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        serde_json::from_str(&fs_err::read_to_string(path)?)
+            .with_context(|| format!("Invalid baseline file {}", path.display()))
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, problem: &Problem) -> bool {
+        self.entries.contains(&BaselineEntry::from(problem))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -1,9 +1,15 @@
 #![cfg_attr(target_family = "wasm", allow(unused_imports))]
-use crate::cli::{problem_report, write_detail_report, ReporterBatch};
+use crate::checkpoint::CHECKPOINT_FILE;
+use crate::cli::{
+    problem_report, write_detail_report, write_machine_report, ReportFormat, ReporterBatch,
+};
 use crate::client::{FileCache, NoCache};
+use crate::config::Config;
 use crate::endpoints::get_endpoints;
+use crate::incremental::INDEX_FILE;
 use crate::storage::Storage;
-use crate::{validate_oparl_api, OparlClient, Reporter};
+use crate::visit::Severity;
+use crate::{validate_oparl_api, OparlClient, Reporter, ValidationOptions};
 use anyhow::bail;
 use fs_err as fs;
 use fs_err::File;
@@ -18,57 +24,90 @@ use std::time::Instant;
 
 /// Runs the validation for a single city in the batch job
 /// with optional caching, finally writing the reports
+#[allow(clippy::too_many_arguments)]
 pub async fn validate_oparl_api_cli(
     name: &str,
     entrypoint: &str,
     cache_dir: &Option<PathBuf>,
     reporter: &impl Reporter,
     report: PathBuf,
+    format: ReportFormat,
+    options: &ValidationOptions,
     main_log: Arc<Mutex<File>>,
 ) -> anyhow::Result<()> {
     if let Some(cache_dir) = cache_dir.clone() {
         let prefix = entrypoint.trim_end_matches("system").to_string();
-        let cache = FileCache::new(cache_dir.join(name), prefix);
-        validate_oparl_api::<_, _>(entrypoint, reporter, &OparlClient::new(cache)).await?;
+        let options = ValidationOptions {
+            checkpoint: Some(cache_dir.join(name).join(CHECKPOINT_FILE)),
+            index: Some(cache_dir.join(name).join(INDEX_FILE)),
+            ..options.clone()
+        };
+        let cache = FileCache::new(cache_dir.join(name))?
+            .with_legacy_prefix(prefix)
+            .with_max_age(options.cache_max_age);
+        validate_oparl_api::<_, _>(
+            entrypoint,
+            reporter,
+            &OparlClient::new(cache).with_revalidate(options.revalidate),
+            &options,
+        )
+        .await?;
     } else {
-        validate_oparl_api::<_, _>(entrypoint, reporter, &OparlClient::new(NoCache)).await?;
+        validate_oparl_api::<_, _>(entrypoint, reporter, &OparlClient::new(NoCache), options)
+            .await?;
     }
 
-    let mut report = BufWriter::new(File::create(report)?);
+    let mut report_file = BufWriter::new(File::create(&report)?);
     problem_report(
         reporter.get_storage(),
         &mut *main_log.lock(),
-        &mut report,
+        &mut report_file,
         true,
     )?;
-    write_detail_report(reporter.get_storage(), &mut *main_log.lock(), &mut report)?;
+    write_detail_report(
+        reporter.get_storage(),
+        &mut *main_log.lock(),
+        &mut report_file,
+    )?;
+    if let Some(extension) = format.extension() {
+        let mut machine_report = BufWriter::new(File::create(report.with_extension(extension))?);
+        write_machine_report(reporter.get_storage(), format, &mut machine_report)?;
+    }
 
     Ok(())
 }
 
 #[cfg(not(target_family = "wasm"))]
+#[allow(clippy::too_many_arguments)]
 fn validate_all_entry(
     title: &str,
     url: &str,
     cache_dir: &Option<PathBuf>,
     report: &Path,
     log: &Path,
+    format: ReportFormat,
+    options: &ValidationOptions,
+    config: &Config,
     main_log: Arc<Mutex<File>>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<Severity>> {
     let log_file = log.join(format!("{}.txt", slugify(title)));
-    let reporter = ReporterBatch::new(Storage::new(), File::create(log_file)?, main_log.clone());
+    let reporter = ReporterBatch::new(
+        Storage::with_config(config.clone()),
+        File::create(log_file)?,
+        main_log.clone(),
+    );
     let report = report.join(format!("{}.txt", slugify(title)));
     reporter.println("START");
 
     let rt = tokio::runtime::Runtime::new()?;
     let result: anyhow::Result<()> = rt.block_on(validate_oparl_api_cli(
-        title, url, cache_dir, &reporter, report, main_log,
+        title, url, cache_dir, &reporter, report, format, options, main_log,
     ));
 
     match result {
         Ok(()) => {
             reporter.println("DONE");
-            Ok(())
+            Ok(reporter.get_storage().max_severity())
         }
         Err(err) => {
             reporter.println(&format!("FAILED: {}", err));
@@ -80,13 +119,19 @@ fn validate_all_entry(
     }
 }
 
+/// Validates all known endpoints, returning the severity of the worst problem found, see
+/// [max_severity]
 #[cfg(not(target_family = "wasm"))]
+#[allow(clippy::too_many_arguments)]
 pub async fn validate_all(
     cache_dir: &Option<PathBuf>,
     report: &Path,
     log: &Path,
     quiet: bool,
-) -> anyhow::Result<()> {
+    format: ReportFormat,
+    options: &ValidationOptions,
+    config: &Config,
+) -> anyhow::Result<Option<Severity>> {
     if quiet {
         bail!("Unsupported quiet");
     }
@@ -116,15 +161,25 @@ pub async fn validate_all(
         env!("CARGO_PKG_NAME"),
         env!("CARGO_PKG_VERSION")
     )?;
-    let results: Vec<anyhow::Result<()>> = endpoints
+    let results: Vec<anyhow::Result<Option<Severity>>> = endpoints
         .par_iter()
         .map(|(title, url)| {
             writeln!(main_log.lock(), "Started {} ({})", title, url)?;
             let start = Instant::now();
-            let result = validate_all_entry(title, url, cache_dir, report, log, main_log.clone());
+            let result = validate_all_entry(
+                title,
+                url,
+                cache_dir,
+                report,
+                log,
+                format,
+                options,
+                config,
+                main_log.clone(),
+            );
             let end = Instant::now();
             match result {
-                Ok(()) => {
+                Ok(max_severity) => {
                     writeln!(
                         main_log.lock(),
                         "DONE {} in {}s ({})",
@@ -132,7 +187,7 @@ pub async fn validate_all(
                         (end - start).as_secs(),
                         url
                     )?;
-                    Ok(())
+                    Ok(max_severity)
                 }
                 Err(err) => {
                     // Write the error string all at once so it's one block and doesn't get interleaved by other threads
@@ -156,5 +211,17 @@ pub async fn validate_all(
         err
     )?;
 
-    Ok(())
+    Ok(max_severity(&results))
+}
+
+/// The severity of the worst problem over all endpoints. An endpoint whose validation failed
+/// counts as an error, since we don't know which problems it has.
+pub fn max_severity(results: &[anyhow::Result<Option<Severity>>]) -> Option<Severity> {
+    results
+        .iter()
+        .filter_map(|result| match result {
+            Ok(max_severity) => *max_severity,
+            Err(_) => Some(Severity::Error),
+        })
+        .max()
 }
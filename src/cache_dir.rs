@@ -0,0 +1,397 @@
+//! The `cache` subcommand, to inspect and clean up the cache directories from `--cache`
+//!
+//! The directory is either the cache of one endpoint or the directory from `all`, which has
+//! one cache per endpoint. Don't run this while a validation uses the cache.
+
+use crate::checkpoint::CHECKPOINT_FILE;
+use crate::client::{
+    is_expired, modified_time, FileCache, ManifestEntry, MANIFEST_FILE, RESPONSES_DIR,
+};
+use crate::incremental::INDEX_FILE;
+use crate::lang::{tr, Lang};
+use crate::MISSING_IDS_FILE;
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use fs_err::File;
+use serde::de::IgnoredAny;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Subcommand)]
+pub enum CacheAction {
+    /// List the cached endpoints with the number of responses, the size and the age
+    List { cache_dir: PathBuf },
+    /// Check that every cached file is valid json, exits with code 2 otherwise
+    Verify { cache_dir: PathBuf },
+    /// Remove the responses older than the given age so that the next run loads them again
+    Prune {
+        cache_dir: PathBuf,
+        /// e.g. `30d`, `12h` or `2w`
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Duration,
+    },
+    /// Forget the missing objects that couldn't be loaded, so that the next run tries again
+    InvalidateMissing {
+        cache_dir: PathBuf,
+        /// Only forget the urls containing one of these, e.g. a host or `/paper/`
+        patterns: Vec<String>,
+    },
+}
+
+impl CacheAction {
+    /// Runs the action on each endpoint in the directory, returning whether `verify` found
+    /// invalid files
+    pub fn run(&self, out: &mut impl Write, lang: Lang) -> Result<bool> {
+        let cache_dir = match self {
+            CacheAction::List { cache_dir }
+            | CacheAction::Verify { cache_dir }
+            | CacheAction::Prune { cache_dir, .. }
+            | CacheAction::InvalidateMissing { cache_dir, .. } => cache_dir,
+        };
+        let dirs = endpoint_dirs(cache_dir)?;
+        if dirs.is_empty() {
+            writeln!(
+                out,
+                "{}",
+                tr!(
+                    lang,
+                    "Keine Caches in {}",
+                    "No caches in {}",
+                    cache_dir.display()
+                )
+            )?;
+        }
+        let mut invalid = 0;
+        for dir in dirs {
+            let name = match dir.strip_prefix(cache_dir) {
+                Ok(name) if name != Path::new("") => name.display().to_string(),
+                _ => dir.display().to_string(),
+            };
+            let summary = match self {
+                CacheAction::List { .. } => CacheStats::load(&dir)?.summary(lang),
+                CacheAction::Verify { .. } => {
+                    let invalid_files = verify(&dir, lang)?;
+                    for (path, error) in &invalid_files {
+                        writeln!(out, "{}: {}", path.display(), error)?;
+                    }
+                    invalid += invalid_files.len();
+                    tr!(
+                        lang,
+                        "{} fehlerhafte Dateien",
+                        "{} invalid files",
+                        invalid_files.len()
+                    )
+                }
+                CacheAction::Prune { older_than, .. } => {
+                    let removed = prune(&dir, *older_than)?;
+                    tr!(lang, "{} Dateien entfernt", "Removed {} files", removed)
+                }
+                CacheAction::InvalidateMissing { patterns, .. } => {
+                    let removed = invalidate_missing(&dir, patterns)?;
+                    tr!(
+                        lang,
+                        "{} fehlende Objekte vergessen",
+                        "Forgot {} missing objects",
+                        removed
+                    )
+                }
+            };
+            writeln!(out, "{}: {}", name, summary)?;
+        }
+        Ok(invalid > 0)
+    }
+}
+
+/// The units of ages, e.g. `30d`
+const AGE_UNITS: [(char, u64); 5] = [
+    ('w', 7 * 24 * 3600),
+    ('d', 24 * 3600),
+    ('h', 3600),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// Parses an age such as `30d`, `12h` or `2w`
+pub fn parse_age(age: &str) -> Result<Duration, String> {
+    let error = || format!("Invalid age {}, use e.g. 30d, 12h, 45m or 2w", age);
+    let unit = age.chars().last().ok_or_else(error)?;
+    let (_, unit_seconds) = AGE_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .ok_or_else(error)?;
+    let number: u64 = age[..age.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| error())?;
+    let seconds = number.checked_mul(*unit_seconds).ok_or_else(error)?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Formats an age in its largest unit, rounded down
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let (unit, unit_seconds) = AGE_UNITS
+        .iter()
+        .find(|(_, unit_seconds)| seconds >= *unit_seconds)
+        .unwrap_or(&('s', 1));
+    format!("{}{}", seconds / unit_seconds, unit)
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let units = ["KB", "MB", "GB"];
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, units[unit])
+}
+
+fn age(time: SystemTime) -> Duration {
+    SystemTime::now().duration_since(time).unwrap_or_default()
+}
+
+/// Whether the directory is the cache of an endpoint, including caches from older versions
+fn is_endpoint_cache(dir: &Path) -> bool {
+    [
+        MANIFEST_FILE,
+        "system.json",
+        MISSING_IDS_FILE,
+        CHECKPOINT_FILE,
+        INDEX_FILE,
+    ]
+    .iter()
+    .any(|file| dir.join(file).is_file())
+}
+
+/// The directory itself if it's the cache of an endpoint, otherwise the caches in it
+fn endpoint_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if is_endpoint_cache(dir) {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+    let mut dirs = Vec::new();
+    for entry in fs_err::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && is_endpoint_cache(&path) {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// All files below the directory, sorted
+fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs_err::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether the file is a response stored by older versions, which had no manifest
+fn is_legacy_response(dir: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(dir) else {
+        return false;
+    };
+    relative
+        .extension()
+        .is_some_and(|extension| extension == "json")
+        && !relative.starts_with(RESPONSES_DIR)
+        && ![CHECKPOINT_FILE, INDEX_FILE, MISSING_IDS_FILE]
+            .iter()
+            .any(|file| relative == Path::new(file))
+}
+
+fn load_missing_ids(dir: &Path) -> Result<Option<HashMap<String, bool>>> {
+    let path = dir.join(MISSING_IDS_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let missing_ids = serde_json::from_slice(&fs_err::read(&path)?)
+        .with_context(|| format!("{} is corrupted", path.display()))?;
+    Ok(Some(missing_ids))
+}
+
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    pub responses: usize,
+    /// The size of all files, including the checkpoint and the index
+    pub bytes: u64,
+    /// When the oldest and the newest response were stored
+    pub oldest: Option<SystemTime>,
+    pub newest: Option<SystemTime>,
+    pub missing_ids: usize,
+}
+
+impl CacheStats {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut stats = CacheStats::default();
+        let mut times = Vec::new();
+        for entry in FileCache::new(dir.to_path_buf())?.entries() {
+            stats.responses += 1;
+            times.extend(entry.fetched_at());
+        }
+        for path in files(dir)? {
+            stats.bytes += fs_err::metadata(&path)?.len();
+            if is_legacy_response(dir, &path) {
+                stats.responses += 1;
+                times.extend(modified_time(&path));
+            }
+        }
+        stats.oldest = times.iter().min().copied();
+        stats.newest = times.iter().max().copied();
+        // A corrupted file is reported by verify
+        stats.missing_ids = load_missing_ids(dir)
+            .ok()
+            .flatten()
+            .map_or(0, |missing_ids| missing_ids.len());
+        Ok(stats)
+    }
+
+    fn summary(&self, lang: Lang) -> String {
+        let ages = match (self.newest, self.oldest) {
+            (Some(newest), Some(oldest)) => tr!(
+                lang,
+                ", {} bis {} alt",
+                ", {} to {} old",
+                format_age(age(newest)),
+                format_age(age(oldest))
+            ),
+            _ => String::new(),
+        };
+        tr!(
+            lang,
+            "{} Antworten, {}{}, {} fehlende Objekte gemerkt",
+            "{} responses, {}{}, {} missing objects remembered",
+            self.responses,
+            format_size(self.bytes),
+            ages,
+            self.missing_ids
+        )
+    }
+}
+
+/// Checks that every json file is valid and that the manifest only points to existing files,
+/// returning the invalid files with the error
+pub fn verify(dir: &Path, lang: Lang) -> Result<Vec<(PathBuf, String)>> {
+    let mut invalid = Vec::new();
+    let manifest_path = dir.join(MANIFEST_FILE);
+    if manifest_path.is_file() {
+        let lines = BufReader::new(File::open(&manifest_path)?).lines();
+        for (number, line) in lines.enumerate() {
+            if let Err(err) = serde_json::from_str::<ManifestEntry>(&line?) {
+                let error = tr!(lang, "Zeile {}: {}", "line {}: {}", number + 1, err);
+                invalid.push((manifest_path.clone(), error));
+            }
+        }
+    }
+    for entry in FileCache::new(dir.to_path_buf())?.entries() {
+        let path = dir.join(&entry.file);
+        if !path.is_file() {
+            let error = tr!(
+                lang,
+                "fehlt, ist im Manifest für {}",
+                "is missing, it's in the manifest for {}",
+                entry.url
+            );
+            invalid.push((path, error));
+        }
+    }
+    for path in files(dir)? {
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            if let Err(err) = serde_json::from_slice::<IgnoredAny>(&fs_err::read(&path)?) {
+                invalid.push((path, err.to_string()));
+            }
+        }
+    }
+    Ok(invalid)
+}
+
+/// Removes the responses older than `max_age`, the response files the manifest doesn't point
+/// to and `missing_ids.json` if it's older than `max_age`. The manifest is rewritten with one
+/// line per url. Returns the number of removed files.
+pub fn prune(dir: &Path, max_age: Duration) -> Result<usize> {
+    let kept: Vec<ManifestEntry> = FileCache::new(dir.to_path_buf())?
+        .entries()
+        .into_iter()
+        .filter(|entry| !is_expired(entry.fetched_at(), max_age))
+        .collect();
+    let kept_files: HashSet<PathBuf> = kept.iter().map(|entry| dir.join(&entry.file)).collect();
+    // The manifest must not point to removed files, so we rewrite it first
+    if dir.join(MANIFEST_FILE).is_file() {
+        write_manifest(dir, &kept)?;
+    }
+
+    let mut removed = 0;
+    for path in files(dir)? {
+        let remove = if path.starts_with(dir.join(RESPONSES_DIR)) {
+            !kept_files.contains(&path)
+        } else if is_legacy_response(dir, &path) || path == dir.join(MISSING_IDS_FILE) {
+            is_expired(modified_time(&path), max_age)
+        } else {
+            false
+        };
+        if remove {
+            fs_err::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Writes to a temporary file first, so a crash while writing keeps the old manifest
+fn write_manifest(dir: &Path, entries: &[ManifestEntry]) -> Result<()> {
+    let path = dir.join(MANIFEST_FILE);
+    let temp_path = path.with_extension("ndjson.tmp");
+    let mut writer = BufWriter::new(File::create(&temp_path)?);
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    drop(writer);
+    fs_err::rename(temp_path, path)?;
+    Ok(())
+}
+
+/// Removes the urls containing one of the patterns from `missing_ids.json`, or all urls
+/// without patterns, so that the next run requests them again. Returns the number of removed
+/// urls.
+pub fn invalidate_missing(dir: &Path, patterns: &[String]) -> Result<usize> {
+    let Some(mut missing_ids) = load_missing_ids(dir)? else {
+        return Ok(0);
+    };
+    let before = missing_ids.len();
+    missing_ids.retain(|url, _| {
+        !patterns.is_empty()
+            && !patterns
+                .iter()
+                .any(|pattern| url.contains(pattern.as_str()))
+    });
+    let removed = before - missing_ids.len();
+    let path = dir.join(MISSING_IDS_FILE);
+    if missing_ids.is_empty() {
+        fs_err::remove_file(path)?;
+    } else if removed > 0 {
+        fs_err::write(path, serde_json::to_vec(&missing_ids)?)?;
+    }
+    Ok(removed)
+}
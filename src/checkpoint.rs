@@ -0,0 +1,254 @@
+//! Checkpoints of a running validation, so that `--resume` can continue after a crash or Ctrl-C
+//!
+//! A checkpoint contains the state of [Storage] (except config and baseline), the position in
+//! each external list that isn't finished yet and the finished steps. Checkpoints are only
+//! taken between two pages, so the problems always match the positions in the lists. Taking
+//! the snapshot is quick, serializing and writing it happens on a blocking thread.
+
+use crate::consistency::Sample;
+use crate::filters::FILTERS;
+use crate::incremental::IndexedObject;
+use crate::lang::Lang;
+use crate::pagination::PaginationChecker;
+use crate::schema::{
+    AgendaItem, Body, Consultation, File, LegislativeTerm, Location, Meeting, Membership,
+    Organization, Paper, Person, System,
+};
+use crate::storage::{FieldLinks, ListObject, Storage};
+use crate::visit::{OparlObject, Problem};
+use crate::BACKLINKS;
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufWriter, Write};
+use std::iter;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// The name of the checkpoint in the cache directory
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// While going through the lists, a checkpoint is written at most this often by default, so
+/// that large checkpoints don't slow down the validation
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Only one checkpoint is written at a time, they share the temporary file
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Where we are in an external list
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ListProgress {
+    /// The page to load next, `None` once we're through the list
+    pub next_url: Option<String>,
+    pub object_count: usize,
+    pub page_count: usize,
+    pub pagination: PaginationChecker,
+    pub visited_pages: HashSet<String>,
+    /// How often we've seen each id in this list
+    pub id_counts: HashMap<String, usize>,
+    pub sample: Sample,
+    /// The objects of the pages so far for the index of `--incremental`
+    #[serde(default)]
+    pub objects: Vec<IndexedObject>,
+}
+
+impl ListProgress {
+    pub fn new(first_url: &str, sample_size: usize, lang: Lang) -> Self {
+        Self {
+            next_url: Some(first_url.to_string()),
+            object_count: 0,
+            page_count: 0,
+            pagination: PaginationChecker::new(first_url, lang),
+            visited_pages: HashSet::new(),
+            id_counts: HashMap::new(),
+            sample: Sample::new(sample_size),
+            objects: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    problems: Vec<Problem>,
+    known_ids: HashSet<String>,
+    /// Field, url and the objects linking the url
+    known_other_urls: Vec<(String, String, Vec<String>)>,
+    /// Type, url and the objects linking the url
+    linked_ids: Vec<(KnownStr, String, Vec<String>)>,
+    field_links: HashMap<String, FieldLinks>,
+    list_objects: HashMap<String, Vec<ListObject>>,
+    /// Id and type
+    deleted_ids: HashMap<String, KnownStr>,
+    list_progress: HashMap<String, ListProgress>,
+    completed_steps: HashSet<String>,
+    #[serde(default)]
+    indexed_objects: HashMap<String, Vec<IndexedObject>>,
+    #[serde(default)]
+    started: Option<String>,
+}
+
+impl Checkpoint {
+    pub fn from_storage(storage: &Storage) -> Self {
+        Self {
+            problems: storage.problems.lock().clone(),
+            known_ids: storage.known_ids.lock().clone(),
+            known_other_urls: storage
+                .known_other_urls
+                .lock()
+                .iter()
+                .map(|((path, url), sources)| (path.clone(), url.clone(), sources.clone()))
+                .collect(),
+            linked_ids: storage
+                .linked_ids
+                .lock()
+                .iter()
+                .map(|((type_name, url), sources)| {
+                    (KnownStr(type_name), url.clone(), sources.clone())
+                })
+                .collect(),
+            field_links: storage.field_links.lock().clone(),
+            list_objects: storage.list_objects.lock().clone(),
+            deleted_ids: storage
+                .deleted_ids
+                .lock()
+                .iter()
+                .map(|(id, type_name)| (id.clone(), KnownStr(type_name)))
+                .collect(),
+            list_progress: storage.list_progress.lock().clone(),
+            completed_steps: storage.completed_steps.lock().clone(),
+            indexed_objects: storage.indexed_objects.lock().clone(),
+            started: storage.started.lock().clone(),
+        }
+    }
+
+    /// Whether the last checkpoint is older than `interval`, the first one is due right away.
+    /// Counts the checkpoint as written when it's due.
+    pub fn is_due(storage: &Storage, interval: Duration) -> bool {
+        let mut last_checkpoint = storage.last_checkpoint.lock();
+        if last_checkpoint.is_some_and(|last| last.elapsed() < interval) {
+            return false;
+        }
+        *last_checkpoint = Some(Instant::now());
+        true
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        serde_json::from_str(&fs_err::read_to_string(path)?)
+            .with_context(|| format!("Invalid checkpoint {}", path.display()))
+    }
+
+    /// Takes a snapshot of the storage and writes it on a blocking thread
+    pub async fn save(storage: &Storage, path: &Path) -> Result<()> {
+        let checkpoint = Self::from_storage(storage);
+        let path = path.to_path_buf();
+        let write = move || checkpoint.write(&path);
+
+        #[cfg(target_family = "wasm")]
+        let result = write();
+        #[cfg(not(target_family = "wasm"))]
+        let result = tokio::task::spawn_blocking(write).await?;
+
+        result
+    }
+
+    /// Writes to a temporary file first, so a crash while writing keeps the last checkpoint
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let _writing = WRITING.lock();
+        let temp_path = path.with_extension("json.tmp");
+        let mut writer = BufWriter::new(fs_err::File::create(&temp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        drop(writer);
+        fs_err::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    /// Replaces the state of the storage with the checkpoint
+    pub fn restore(self, storage: &Storage) {
+        *storage.problems.lock() = self.problems;
+        *storage.known_ids.lock() = self.known_ids;
+        *storage.known_other_urls.lock() = self
+            .known_other_urls
+            .into_iter()
+            .map(|(path, url, sources)| ((path, url), sources))
+            .collect();
+        *storage.linked_ids.lock() = self
+            .linked_ids
+            .into_iter()
+            .map(|(KnownStr(type_name), url, sources)| ((type_name, url), sources))
+            .collect();
+        *storage.field_links.lock() = self.field_links;
+        *storage.list_objects.lock() = self.list_objects;
+        *storage.deleted_ids.lock() = self
+            .deleted_ids
+            .into_iter()
+            .map(|(id, KnownStr(type_name))| (id, type_name))
+            .collect();
+        *storage.list_progress.lock() = self.list_progress;
+        *storage.completed_steps.lock() = self.completed_steps;
+        *storage.indexed_objects.lock() = self.indexed_objects;
+        *storage.started.lock() = self.started;
+    }
+}
+
+/// A `&'static str` in the checkpoint, such as a type name
+#[derive(Serialize)]
+struct KnownStr(&'static str);
+
+impl<'de> Deserialize<'de> for KnownStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_static(deserializer).map(KnownStr)
+    }
+}
+
+/// All strings that end up in the places that are `&'static str`: the type and field names of
+/// the schema, the backlink fields, the filters and the pagination links
+static KNOWN: OnceLock<BTreeSet<&'static str>> = OnceLock::new();
+
+fn known() -> &'static BTreeSet<&'static str> {
+    KNOWN.get_or_init(|| {
+        let types = [
+            (AgendaItem::type_name(), AgendaItem::field_names()),
+            (Body::type_name(), Body::field_names()),
+            (Consultation::type_name(), Consultation::field_names()),
+            (File::type_name(), File::field_names()),
+            (LegislativeTerm::type_name(), LegislativeTerm::field_names()),
+            (Location::type_name(), Location::field_names()),
+            (Meeting::type_name(), Meeting::field_names()),
+            (Membership::type_name(), Membership::field_names()),
+            (Organization::type_name(), Organization::field_names()),
+            (Paper::type_name(), Paper::field_names()),
+            (Person::type_name(), Person::field_names()),
+            (System::type_name(), System::field_names()),
+        ];
+        let types = types.into_iter().flat_map(|(type_name, field_names)| {
+            iter::once(type_name).chain(field_names.iter().copied())
+        });
+        let backlinks = BACKLINKS
+            .into_iter()
+            .flat_map(|(forward, backward)| [forward, backward]);
+        let filters = FILTERS
+            .into_iter()
+            .flat_map(|(_, since, until)| [since, until]);
+        types
+            .chain(backlinks)
+            .chain(filters)
+            .chain(["limit", "first", "self", "last"])
+            .collect()
+    })
+}
+
+/// For the `&'static str` fields of [crate::visit::ProblemKind]. Only the known strings are
+/// accepted, so that we don't have to leak the strings we read.
+pub(crate) fn deserialize_static<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static str, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    known()
+        .get(value.as_str())
+        .copied()
+        .ok_or_else(|| D::Error::custom(format!("unknown type, field or link name {}", value)))
+}
@@ -1,9 +1,14 @@
+use crate::lang::{tr, Lang};
 use crate::reporter::{ProgressBarWrapper, Reporter};
 use crate::storage::Storage;
+use crate::visit::{Problem, ProblemKind, Severity};
+use clap::ValueEnum;
 use fs_err::File;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::io::Write;
 use std::sync::Arc;
 use std::time::Duration;
@@ -40,8 +45,12 @@ impl Reporter for ReporterCli {
     }
 
     fn add_bar(&self, name: impl Into<Cow<'static, str>>) -> ProgressBarCli {
+        let lang = self.lang();
         let sty = ProgressStyle::default_spinner()
-            .template("{spinner} [{elapsed_precise}] [{prefix}] Page {pos}: {msg}")
+            .template(lang.pick(
+                "{spinner} [{elapsed_precise}] [{prefix}] Seite {pos}: {msg}",
+                "{spinner} [{elapsed_precise}] [{prefix}] Page {pos}: {msg}",
+            ))
             .unwrap();
 
         let name = name.into();
@@ -58,7 +67,7 @@ impl Reporter for ReporterCli {
             ProgressBar::hidden()
         };
 
-        ProgressBarCli::new(progress_bar, name, self.log_file.clone())
+        ProgressBarCli::new(progress_bar, name, self.log_file.clone(), lang)
     }
 
     fn add_message(&self, message: &str) {
@@ -68,34 +77,135 @@ impl Reporter for ReporterCli {
     }
 }
 
+/// One line in the summary with the number of cases and an example
+fn summary_line(group: &(ProblemKind, String), problems: &[Problem], lang: Lang) -> String {
+    tr!(
+        lang,
+        "[{}] {} ({} Fälle). Beispiel: {}",
+        "[{}] {} ({} cases). Example: {}",
+        group.0.code(),
+        group.0.message(&group.1, lang),
+        problems.len(),
+        // All vecs are non-empty
+        problems.iter().min().unwrap().detail
+    )
+}
+
 /// Currently prints to CLI and writes a hardcoded report.md
+///
+/// With a baseline, the summary only contains the new problems and the known ones are listed
+/// in a separate section
 pub fn problem_report(
     storage: &Storage,
     out: &mut impl Write,
     report: &mut impl Write,
     is_final: bool,
 ) -> anyhow::Result<()> {
-    let problem_groups = storage.problems_grouped();
-
-    let summary_header = if is_final {
-        "=== Validierungsreport: Zusammenfassung ==="
-    } else {
-        "=== ZWISCHENSTAND Zusammenfassung Validierungsreport ==="
+    let lang = storage.config.lang;
+    let problem_groups = storage.problems_grouped_by_baseline(false);
+
+    let summary_header = match (is_final, storage.baseline.is_some()) {
+        (true, false) => lang.pick(
+            "=== Validierungsreport: Zusammenfassung ===",
+            "=== Validation report: Summary ===",
+        ),
+        (false, false) => lang.pick(
+            "=== ZWISCHENSTAND Zusammenfassung Validierungsreport ===",
+            "=== INTERIM Summary validation report ===",
+        ),
+        (true, true) => lang.pick(
+            "=== Validierungsreport: Zusammenfassung der neuen Probleme ===",
+            "=== Validation report: Summary of the new problems ===",
+        ),
+        (false, true) => lang.pick(
+            "=== ZWISCHENSTAND Zusammenfassung der neuen Probleme ===",
+            "=== INTERIM Summary of the new problems ===",
+        ),
     };
     writeln!(out, "{}", summary_header)?;
     writeln!(report, "{}", summary_header)?;
     for (group, problems) in problem_groups.iter() {
-        let message = format!(
-            "{} ({} Fälle). Beispiel: {}",
-            group.0.message(&group.1),
-            problems.len(),
-            // All vecs are non-empty
-            problems.iter().min().unwrap().detail
-        );
+        let message = summary_line(group, problems, lang);
         writeln!(out, "{}", message)?;
         writeln!(report, "{}", message)?;
     }
 
+    let severity_counts: Vec<String> = storage
+        .severity_counts()
+        .into_iter()
+        .map(|(severity, count)| {
+            tr!(
+                lang,
+                "{}: {} Fälle",
+                "{}: {} cases",
+                severity.label(lang),
+                count
+            )
+        })
+        .collect();
+    writeln!(out, "{}", severity_counts.join(", "))?;
+    writeln!(report, "{}", severity_counts.join(", "))?;
+
+    if storage.baseline.is_some() {
+        let header = lang.pick(
+            "=== Bekannte Probleme aus der Baseline ===",
+            "=== Known problems from the baseline ===",
+        );
+        writeln!(out, "{}", header)?;
+        writeln!(report, "{}", header)?;
+        for (group, problems) in storage.problems_grouped_by_baseline(true).iter() {
+            let message = summary_line(group, problems, lang);
+            writeln!(out, "{}", message)?;
+            writeln!(report, "{}", message)?;
+        }
+    }
+
+    let deleted_counts = storage.deleted_counts();
+    if !deleted_counts.is_empty() {
+        let header = lang.pick("=== Gelöschte Objekte ===", "=== Deleted objects ===");
+        writeln!(out, "{}", header)?;
+        writeln!(report, "{}", header)?;
+        for (type_name, count) in deleted_counts {
+            writeln!(out, "{}: {}", type_name, count)?;
+            writeln!(report, "{}: {}", type_name, count)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_detail_groups(
+    report: &mut impl Write,
+    problem_groups: &BTreeMap<(ProblemKind, String), Vec<Problem>>,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    for (group, problems) in problem_groups.iter() {
+        let header = tr!(
+            lang,
+            "== [{}] {} ({} Fälle) ==",
+            "== [{}] {} ({} cases) ==",
+            group.0.code(),
+            group.0.message(&group.1, lang),
+            problems.len()
+        );
+        writeln!(report, "{}", header)?;
+        for problem in problems.iter().take(100) {
+            writeln!(report, "{}", problem.detail)?;
+        }
+        if problems.len() > 100 {
+            let more = problems.len() - 100;
+            writeln!(
+                report,
+                "{}",
+                tr!(
+                    lang,
+                    "... und {} weitere Fälle",
+                    "... and {} more cases",
+                    more
+                )
+            )?;
+        }
+    }
     Ok(())
 }
 
@@ -104,24 +214,132 @@ pub fn write_detail_report(
     out: &mut impl Write,
     report: &mut impl Write,
 ) -> anyhow::Result<()> {
-    let problem_groups = storage.problems_grouped();
-
-    writeln!(report, "=== Detailreport: Alle Einzelfälle ===")?;
-    for (group, problems) in problem_groups.iter() {
+    let lang = storage.config.lang;
+    let header = if storage.baseline.is_some() {
+        lang.pick(
+            "=== Detailreport: Alle neuen Einzelfälle ===",
+            "=== Detail report: All new cases ===",
+        )
+    } else {
+        lang.pick(
+            "=== Detailreport: Alle Einzelfälle ===",
+            "=== Detail report: All cases ===",
+        )
+    };
+    writeln!(report, "{}", header)?;
+    write_detail_groups(report, &storage.problems_grouped_by_baseline(false), lang)?;
+    if storage.baseline.is_some() {
         writeln!(
             report,
-            "== {} ({} Fälle) ==",
-            group.0.message(&group.1),
-            problems.len()
+            "{}",
+            lang.pick(
+                "=== Detailreport: Bekannte Probleme aus der Baseline ===",
+                "=== Detail report: Known problems from the baseline ===",
+            )
         )?;
-        for problem in problems.iter().take(100) {
-            writeln!(report, "{}", problem.detail)?;
+        write_detail_groups(report, &storage.problems_grouped_by_baseline(true), lang)?;
+    }
+    writeln!(
+        out,
+        "\n{}",
+        lang.pick(
+            "Alle einzelnen Fälle finden sich in report.txt",
+            "All single cases can be found in report.txt",
+        )
+    )?;
+    Ok(())
+}
+
+/// Additional report format next to the human-readable report.txt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Only the text report
+    #[default]
+    Text,
+    /// Additionally a json array with all problems
+    Json,
+    /// Additionally newline delimited json with one problem per line
+    Ndjson,
+}
+
+impl ReportFormat {
+    /// File extension of the machine-readable report, if there is one
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            ReportFormat::Text => None,
+            ReportFormat::Json => Some("json"),
+            ReportFormat::Ndjson => Some("ndjson"),
         }
-        if problems.len() > 100 {
-            writeln!(report, "... und {} weitere Fälle", problems.len() - 100)?;
+    }
+}
+
+/// One problem in the machine-readable reports
+///
+/// Contains the stable kind name in addition to the message in the configured language so
+/// tools don't need to parse the text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportEntry {
+    /// The stable rule code, e.g. `OPARL007`
+    #[serde(default)]
+    pub code: String,
+    pub kind: String,
+    /// The oparl type for kinds such as `ObjectMissing`
+    pub type_name: Option<String>,
+    /// With the overrides from the config applied
+    pub severity: Severity,
+    /// Whether the problem is already in the baseline
+    #[serde(default)]
+    pub known: bool,
+    pub message: String,
+    pub path: String,
+    pub detail: String,
+    pub url: Option<String>,
+    pub id: Option<String>,
+}
+
+impl ReportEntry {
+    pub fn new(problem: &Problem, storage: &Storage) -> Self {
+        Self {
+            code: problem.kind.code().to_string(),
+            kind: problem.kind.name().to_string(),
+            type_name: problem.kind.type_name().map(ToString::to_string),
+            severity: storage.config.severity(&problem.kind),
+            known: storage.is_known(problem),
+            message: problem.kind.message(&problem.path, storage.config.lang),
+            path: problem.path.clone(),
+            detail: problem.detail.clone(),
+            url: problem.url.clone(),
+            id: problem.id.clone(),
+        }
+    }
+}
+
+/// Writes all problems in the same order as the text report as json or ndjson
+pub fn write_machine_report(
+    storage: &Storage,
+    format: ReportFormat,
+    report: &mut impl Write,
+) -> anyhow::Result<()> {
+    let entries: Vec<ReportEntry> = storage
+        .problems_grouped()
+        .values()
+        .flatten()
+        .map(|problem| ReportEntry::new(problem, storage))
+        .collect();
+
+    match format {
+        ReportFormat::Text => {}
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *report, &entries)?;
+            writeln!(report)?;
+        }
+        ReportFormat::Ndjson => {
+            for entry in entries {
+                serde_json::to_writer(&mut *report, &entry)?;
+                writeln!(report)?;
+            }
         }
     }
-    writeln!(out, "\nAlle einzelnen Fälle finden sich in report.txt")?;
     Ok(())
 }
 
@@ -129,6 +347,7 @@ pub struct ProgressBarCli {
     progress_bar: ProgressBar,
     name: String,
     log_file: Option<Arc<Mutex<File>>>,
+    lang: Lang,
 }
 
 impl ProgressBarCli {
@@ -136,11 +355,13 @@ impl ProgressBarCli {
         progress_bar: ProgressBar,
         name: impl Into<Cow<'static, str>>,
         log_file: Option<Arc<Mutex<File>>>,
+        lang: Lang,
     ) -> Self {
         Self {
             progress_bar,
             name: name.into().to_string(),
             log_file,
+            lang,
         }
     }
 }
@@ -168,7 +389,10 @@ impl ProgressBarWrapper for ProgressBarCli {
 
     fn set_length(&self, len: u64) {
         let style = ProgressStyle::default_spinner()
-            .template("{spinner} [{elapsed_precise}] [{prefix:<12}] Page {pos:>3}/{len:<3}: {msg}")
+            .template(self.lang.pick(
+                "{spinner} [{elapsed_precise}] [{prefix:<12}] Seite {pos:>3}/{len:<3}: {msg}",
+                "{spinner} [{elapsed_precise}] [{prefix:<12}] Page {pos:>3}/{len:<3}: {msg}",
+            ))
             .unwrap();
         self.progress_bar.set_style(style);
         self.progress_bar.set_length(len);
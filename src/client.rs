@@ -1,15 +1,27 @@
+use crate::MISSING_IDS_FILE;
 use anyhow::{Context, Error, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
 use fs_err as fs;
 use fs_err::File;
-use reqwest::Client;
+use parking_lot::Mutex;
+use reqwest::header::{
+    HeaderMap, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, StatusCode};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::io::{BufWriter, Read, Write};
-use std::path::PathBuf;
+use sha1_smol::Sha1;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
-/// Split into normal methods and inner so that the file cache can do some prefix trimming
-/// while endpoints.yml doesn't need to go through that
+/// Split into normal methods and inner so that the file cache can map urls to files while
+/// endpoints.yml and missing_ids.json are stored under their name
 pub trait Cache {
     fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>>;
     fn set_inner(&self, key: &str, data: &[u8]) -> Result<()>;
@@ -19,64 +31,359 @@ pub trait Cache {
     fn set(&self, url: &str, data: &[u8]) -> Result<()> {
         self.set_inner(url, data)
     }
+    /// The cached response even if it's older than the max age of the cache, so that
+    /// `--revalidate` can ask the server whether it changed
+    fn get_stale(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        self.get(url)
+    }
+    /// The headers of the cached response, if the cache stores them
+    fn get_metadata(&self, _url: &str) -> Result<Option<ResponseMetadata>> {
+        Ok(None)
+    }
+    /// Stores a response together with its headers, caches without metadata only store the body
+    fn set_response(&self, url: &str, data: &[u8], _metadata: &ResponseMetadata) -> Result<()> {
+        self.set(url, data)
+    }
+    /// Marks the cached response as current after the server answered 304 Not Modified, so
+    /// that it counts as fresh for the max age. Caches without timestamps store it again.
+    fn refresh(&self, url: &str, data: &[u8], metadata: &ResponseMetadata) -> Result<()> {
+        self.set_response(url, data, metadata)
+    }
+}
+
+/// The response isn't in a cache that doesn't load missing responses. Asking again gives the
+/// same answer, so such requests aren't retried.
+#[derive(Debug)]
+pub struct NotCached(pub String);
+
+impl fmt::Display for NotCached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not in the cache", self.0)
+    }
+}
+
+impl std::error::Error for NotCached {}
+
+/// The headers of a response that we keep next to the body
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseMetadata {
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl ResponseMetadata {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        Self {
+            content_type: header(CONTENT_TYPE),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+}
+
+/// A line in the manifest of the [FileCache]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub url: String,
+    /// The file with the body, relative to the cache directory
+    pub file: String,
+    /// When we stored the response, in RFC 3339
+    pub fetched: String,
+    #[serde(flatten)]
+    pub metadata: ResponseMetadata,
 }
 
+impl ManifestEntry {
+    /// The entry for a response we're storing now
+    pub fn new(url: &str, metadata: &ResponseMetadata) -> Self {
+        Self {
+            url: url.to_string(),
+            file: format!("{}/{}.json", RESPONSES_DIR, Sha1::from(url).digest()),
+            fetched: DateTime::<Utc>::from(SystemTime::now())
+                .to_rfc3339_opts(SecondsFormat::Secs, false),
+            metadata: metadata.clone(),
+        }
+    }
+
+    /// When we stored the response, `None` if the timestamp is invalid
+    pub fn fetched_at(&self) -> Option<SystemTime> {
+        DateTime::parse_from_rfc3339(&self.fetched)
+            .ok()
+            .map(SystemTime::from)
+    }
+}
+
+/// Whether something stored at that time is older than `max_age`, unknown times count as expired
+pub(crate) fn is_expired(stored: Option<SystemTime>, max_age: Duration) -> bool {
+    match stored {
+        Some(stored) => SystemTime::now()
+            .duration_since(stored)
+            .is_ok_and(|age| age > max_age),
+        None => true,
+    }
+}
+
+/// The name of the manifest in the cache directory
+pub const MANIFEST_FILE: &str = "manifest.ndjson";
+/// The directory with the response bodies in the cache directory
+pub(crate) const RESPONSES_DIR: &str = "responses";
+
+/// Reads the entries of a manifest by url
+pub(crate) fn read_manifest(reader: impl BufRead) -> Result<HashMap<String, ManifestEntry>> {
+    let mut manifest = HashMap::new();
+    for line in reader.lines() {
+        // A crash while appending can leave a partial last line
+        let Ok(entry) = serde_json::from_str::<ManifestEntry>(&line?) else {
+            continue;
+        };
+        manifest.insert(entry.url.clone(), entry);
+    }
+    Ok(manifest)
+}
+
+/// Where older versions stored the response, relative to the cache directory
+pub(crate) fn legacy_key(prefix: Option<&str>, url: &str) -> Option<String> {
+    let stripped_url = url.strip_prefix(prefix?)?;
+    Some(
+        Path::new(stripped_url)
+            .with_extension("json")
+            .to_string_lossy()
+            .to_string(),
+    )
+}
+
+/// Finds the url of the cached system among the cached urls, trying those ending with
+/// `system` first. Caches from older versions have the system in `system.json`.
+pub fn find_system<C: Cache>(cache: &C, mut urls: Vec<String>) -> Result<Option<String>> {
+    let system_type = |bytes: &[u8]| {
+        let value: Value = serde_json::from_slice(bytes).ok()?;
+        let is_system = value.get("type")?.as_str()?.ends_with("/System");
+        is_system.then(|| value.get("id")?.as_str().map(ToString::to_string))?
+    };
+    urls.sort_by_key(|url| !url.trim_end_matches('/').ends_with("system"));
+    for url in urls {
+        if let Some(bytes) = cache.get(&url)? {
+            if system_type(&bytes).is_some() {
+                return Ok(Some(url));
+            }
+        }
+    }
+    Ok(cache
+        .get_inner("system.json")?
+        .and_then(|bytes| system_type(&bytes)))
+}
+
+/// Stores the responses under the sha1 of their url, so any url fits, with a manifest that maps
+/// the urls to the files
+///
+/// The manifest is only appended to, one json line per stored response, and the last line for
+/// a url wins. Caches from older versions stored the responses under the url without the
+/// prefix of the entrypoint, those are still read with [FileCache::with_legacy_prefix].
 pub struct FileCache {
     cache_dir: PathBuf,
-    prefix: String,
+    /// The stored responses by url
+    manifest: Mutex<HashMap<String, ManifestEntry>>,
+    legacy_prefix: Option<String>,
+    /// Responses older than this are treated as missing and loaded again
+    max_age: Option<Duration>,
 }
 
-/// Stupid caching implementation that assumes that all urls share a common prefix
-/// (which is currently the case for all implementations)
 impl FileCache {
-    pub fn new(cache_dir: PathBuf, prefix: String) -> Self {
-        Self { cache_dir, prefix }
+    pub fn new(cache_dir: PathBuf) -> Result<Self> {
+        let manifest_file = cache_dir.join(MANIFEST_FILE);
+        let manifest = if manifest_file.is_file() {
+            read_manifest(BufReader::new(File::open(&manifest_file)?))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            cache_dir,
+            manifest: Mutex::new(manifest),
+            legacy_prefix: None,
+            max_age: None,
+        })
+    }
+
+    /// Also reads the responses cached by older versions, which assumed that all urls start
+    /// with the prefix of the entrypoint
+    pub fn with_legacy_prefix(self, prefix: String) -> Self {
+        Self {
+            legacy_prefix: Some(prefix),
+            ..self
+        }
     }
 
-    fn get_cache_file(&self, url: &str) -> Result<PathBuf> {
-        let stripped_url = url.strip_prefix(&self.prefix).context(format!(
-            "{} does not start with prefix {}",
-            url, self.prefix
-        ))?;
-        let cache_file = self.cache_dir.join(stripped_url).with_extension("json");
-        Ok(cache_file)
+    /// Loads responses that are older than `max_age` again. Responses from older versions have
+    /// no timestamp in the manifest, for those the modification time of the file counts, as for
+    /// `missing_ids.json`.
+    pub fn with_max_age(self, max_age: Option<Duration>) -> Self {
+        Self { max_age, ..self }
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// All stored responses, sorted by url
+    pub fn entries(&self) -> Vec<ManifestEntry> {
+        let mut entries: Vec<_> = self.manifest.lock().values().cloned().collect();
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        entries
+    }
+
+    fn legacy_file(&self, url: &str) -> Option<PathBuf> {
+        Some(
+            self.cache_dir
+                .join(legacy_key(self.legacy_prefix.as_deref(), url)?),
+        )
+    }
+
+    fn append_to_manifest(&self, entry: ManifestEntry) -> Result<()> {
+        let mut manifest = self.manifest.lock();
+        let mut manifest_file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.cache_dir.join(MANIFEST_FILE))?;
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        manifest_file.write_all(&line)?;
+        manifest.insert(entry.url.clone(), entry);
+        Ok(())
+    }
+
+    fn get_with_max_age(&self, url: &str, max_age: Option<Duration>) -> Result<Option<Vec<u8>>> {
+        let entry = self.manifest.lock().get(url).cloned();
+        let expired = |stored| max_age.is_some_and(|max_age| is_expired(stored, max_age));
+        if let Some(entry) = entry {
+            if expired(entry.fetched_at()) {
+                return Ok(None);
+            }
+            return self.get_inner(&entry.file);
+        }
+        match self.legacy_file(url) {
+            Some(legacy_file) if legacy_file.is_file() => {
+                if expired(modified_time(&legacy_file)) {
+                    return Ok(None);
+                }
+                read_if_exists(&legacy_file)
+            }
+            _ => Ok(None),
+        }
     }
 }
 
+fn read_if_exists(path: &Path) -> Result<Option<Vec<u8>>> {
+    if path.is_file() {
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(Some(bytes))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The modification time of a file, `None` if the platform doesn't have one
+pub(crate) fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Makes the names of the temporary files of [FileCache] unique within the process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 impl Cache for FileCache {
     fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
-        if PathBuf::from(key).is_file() {
-            let mut file = File::open(key)?;
-            let mut bytes = Vec::new();
-            file.read_to_end(&mut bytes)?;
-            Ok(Some(bytes))
-        } else {
-            Ok(None)
+        let path = self.cache_dir.join(key);
+        // The linked objects that couldn't be loaded are requested again like old responses
+        if key == MISSING_IDS_FILE
+            && self
+                .max_age
+                .is_some_and(|max_age| is_expired(modified_time(&path), max_age))
+        {
+            return Ok(None);
         }
+        read_if_exists(&path)
     }
 
     fn set_inner(&self, key: &str, data: &[u8]) -> Result<()> {
-        let path = PathBuf::from(key);
+        let path = self.cache_dir.join(key);
         let parent_dir = path
             .parent()
             .context(format!("No parent directory for {}", key))?;
         fs::create_dir_all(parent_dir)?;
-        BufWriter::new(File::create(key)?).write_all(data)?;
+        // Written completely to a temporary file first, so that a crash never leaves a
+        // truncated file under the key. Concurrent writes of the same key each get their own
+        // temporary file.
+        let temp_path = path.with_file_name(format!(
+            "{}.{}.tmp",
+            path.file_name()
+                .context(format!("No file name for {}", key))?
+                .to_string_lossy(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        writer.write_all(data)?;
+        let file = writer.into_inner().map_err(|err| err.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(temp_path, path)?;
 
         Ok(())
     }
 
     fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
-        let cache_file = self.get_cache_file(url)?;
-        self.get_inner(cache_file.to_str().unwrap())
+        self.get_with_max_age(url, self.max_age)
+    }
+
+    fn get_stale(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        self.get_with_max_age(url, None)
     }
 
     fn set(&self, url: &str, data: &[u8]) -> Result<()> {
-        let cache_file = self.get_cache_file(url)?;
-        self.set_inner(cache_file.to_str().unwrap(), data)
+        self.set_response(url, data, &ResponseMetadata::default())
+    }
+
+    fn get_metadata(&self, url: &str) -> Result<Option<ResponseMetadata>> {
+        Ok(self
+            .manifest
+            .lock()
+            .get(url)
+            .map(|entry| entry.metadata.clone()))
+    }
+
+    fn set_response(&self, url: &str, data: &[u8], metadata: &ResponseMetadata) -> Result<()> {
+        let entry = ManifestEntry::new(url, metadata);
+        // The body is complete and on disk before the manifest points to it
+        self.set_inner(&entry.file, data)?;
+        self.append_to_manifest(entry)
+    }
+
+    /// Only a new manifest line with the current time for the unchanged file. Responses from
+    /// older versions aren't in the manifest yet, those are stored like new ones.
+    fn refresh(&self, url: &str, data: &[u8], metadata: &ResponseMetadata) -> Result<()> {
+        let entry = self.manifest.lock().get(url).cloned();
+        match entry {
+            Some(entry) => self.append_to_manifest(ManifestEntry {
+                file: entry.file,
+                ..ManifestEntry::new(url, metadata)
+            }),
+            None => self.set_response(url, data, metadata),
+        }
     }
 }
 
+/// Sent with all requests, some servers reject requests without a user agent
+#[cfg_attr(target_family = "wasm", allow(dead_code))]
+pub(crate) const USER_AGENT: &str =
+    "oparl-validator/oparl-validator Konstantin Schütze <konstantin@schuetze.link>";
+
 /// Noop cache
 pub struct NoCache;
 
@@ -95,6 +402,24 @@ pub struct OparlClient<C: Cache> {
     pub cache: C,
     /// Actual reqwest client
     client: Client,
+    /// Whether to ask the server if cached responses are still current
+    revalidate: bool,
+    /// The urls we've already loaded or revalidated in this run
+    revalidated: Mutex<HashSet<String>>,
+    revalidations: Mutex<Vec<(String, Revalidation)>>,
+}
+
+/// How the server answered when we loaded a cached response again with `--revalidate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Revalidation {
+    /// 304, we keep the cached response
+    NotModified,
+    /// The response changed, or the cached response had no validators to ask conditionally
+    Modified,
+    /// The full response even though it didn't change, the server ignores conditional requests
+    Ignored,
+    /// The response has neither `ETag` nor `Last-Modified`, so it can't be revalidated
+    NoValidators,
 }
 
 impl<C: Cache> OparlClient<C> {
@@ -102,9 +427,23 @@ impl<C: Cache> OparlClient<C> {
         Self {
             cache,
             client: Client::new(),
+            revalidate: false,
+            revalidated: Mutex::new(HashSet::new()),
+            revalidations: Mutex::new(Vec::new()),
         }
     }
 
+    /// Loads each cached response again once per run, with `If-None-Match` and
+    /// `If-Modified-Since` so that the server only sends it if it changed
+    pub fn with_revalidate(self, revalidate: bool) -> Self {
+        Self { revalidate, ..self }
+    }
+
+    /// How the server answered the responses we loaded or revalidated since the last call
+    pub fn take_revalidations(&self) -> Vec<(String, Revalidation)> {
+        std::mem::take(&mut *self.revalidations.lock())
+    }
+
     /// This function has to failure modes: The outer is for when the http/cache failed,
     /// the inner is for when deserialization into the schema failed and returns
     /// the raw serde value together with the error
@@ -112,14 +451,50 @@ impl<C: Cache> OparlClient<C> {
         &self,
         url: &str,
     ) -> Result<Result<T, (Value, String)>> {
-        if let Some(bytes) = self.cache.get(url)? {
-            self.deserialize(bytes).await
+        // Responses older than `--cache-max-age` are revalidated like the others instead of
+        // being loaded again completely
+        let cached = if self.revalidate {
+            self.cache.get_stale(url)?
         } else {
-            let bytes = self.get_bytes(url).await?;
-            self.cache.set(url, &bytes)?;
-
-            self.deserialize(bytes).await
-        }
+            self.cache.get(url)?
+        };
+        let bytes = match cached {
+            Some(cached) if !self.revalidate || self.revalidated.lock().contains(url) => cached,
+            cached => {
+                let cached_metadata = match cached {
+                    Some(_) => self.cache.get_metadata(url)?,
+                    None => None,
+                };
+                let bytes = match self.get_response(url, cached_metadata.as_ref()).await? {
+                    Some((bytes, metadata)) => {
+                        self.cache.set_response(url, &bytes, &metadata)?;
+                        if self.revalidate {
+                            let revalidation =
+                                revalidation(cached.as_deref(), cached_metadata, &bytes, &metadata);
+                            self.revalidations.lock().extend(
+                                revalidation.map(|revalidation| (url.to_string(), revalidation)),
+                            );
+                        }
+                        bytes
+                    }
+                    None => {
+                        self.revalidations
+                            .lock()
+                            .push((url.to_string(), Revalidation::NotModified));
+                        let cached =
+                            cached.context("304 Not Modified without a conditional request")?;
+                        self.cache
+                            .refresh(url, &cached, &cached_metadata.unwrap_or_default())?;
+                        cached
+                    }
+                };
+                if self.revalidate {
+                    self.revalidated.lock().insert(url.to_string());
+                }
+                bytes
+            }
+        };
+        self.deserialize(bytes).await
     }
 
     async fn deserialize<T: 'static + DeserializeOwned + Send>(
@@ -144,25 +519,58 @@ impl<C: Cache> OparlClient<C> {
         data
     }
 
-    /// Bare uncached GET
-    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
-        let get_builder = self.client.get(url);
+    /// Bare uncached GET, conditional if we have the headers of a cached response. Returns
+    /// `None` if the server answered that the cached response is still current.
+    async fn get_response(
+        &self,
+        url: &str,
+        cached: Option<&ResponseMetadata>,
+    ) -> Result<Option<(Vec<u8>, ResponseMetadata)>> {
+        let mut get_builder = self.client.get(url);
+        if let Some(etag) = cached.and_then(|cached| cached.etag.as_ref()) {
+            get_builder = get_builder.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = cached.and_then(|cached| cached.last_modified.as_ref()) {
+            get_builder = get_builder.header(IF_MODIFIED_SINCE, last_modified);
+        }
         // We are not allowed to set the user agent in wasm
         #[cfg(not(target_family = "wasm"))]
-        let get_builder = get_builder.header(
-            "User-Agent",
-            "oparl-validator/oparl-validator Konstantin Schütze <konstantin@schuetze.link>",
-        );
+        let get_builder = get_builder.header("User-Agent", USER_AGENT);
         let response = get_builder
             .send()
             .await
             .context("API request failed to send")?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
         let response = response.error_for_status()?;
+        let metadata = ResponseMetadata::from_headers(response.headers());
         let bytes = response
             .bytes()
             .await
             .context("API request failed to respond")?
             .to_vec();
-        Ok(bytes)
+        Ok(Some((bytes, metadata)))
+    }
+}
+
+/// Compares a response that we loaded with `--revalidate` with the cached one, `None` if there
+/// was nothing cached and the response can be revalidated next time
+fn revalidation(
+    cached: Option<&[u8]>,
+    cached_metadata: Option<ResponseMetadata>,
+    bytes: &[u8],
+    metadata: &ResponseMetadata,
+) -> Option<Revalidation> {
+    if metadata.etag.is_none() && metadata.last_modified.is_none() {
+        return Some(Revalidation::NoValidators);
+    }
+    let cached = cached?;
+    let conditional = cached_metadata
+        .is_some_and(|cached| cached.etag.is_some() || cached.last_modified.is_some());
+    if conditional && cached == bytes {
+        Some(Revalidation::Ignored)
+    } else {
+        Some(Revalidation::Modified)
     }
 }
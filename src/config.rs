@@ -0,0 +1,116 @@
+//! Settings from the config file and the command line that change how problems are reported
+
+use crate::lang::Lang;
+use crate::visit::{ProblemKind, Severity};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The config file, e.g.
+///
+/// ```yaml
+/// severity:
+///   UsingHttp: error
+///   OPARL035: warning
+/// ignore:
+///   - OPARL007
+/// lang: en
+/// ```
+///
+/// Rules can be given by their code or by the name of the problem kind
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Overrides of the default severity by rule
+    #[serde(default)]
+    pub severity: HashMap<String, Severity>,
+    /// If not empty, only these rules are checked
+    #[serde(default)]
+    pub select: Vec<String>,
+    /// These rules are not checked
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// The language of messages and reports
+    #[serde(default)]
+    pub lang: Lang,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let config: Config = serde_yaml::from_str(&fs_err::read_to_string(path)?)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        // Normalize everything to the names so we can look them up directly
+        let mut normalized = Config {
+            lang: config.lang,
+            ..Config::default()
+        };
+        for (rule, severity) in config.severity {
+            normalized
+                .severity
+                .insert(rule_name(&rule)?.to_string(), severity);
+        }
+        for rule in config.select {
+            normalized.add_select(&rule)?;
+        }
+        for rule in config.ignore {
+            normalized.add_ignore(&rule)?;
+        }
+        Ok(normalized)
+    }
+
+    /// Applies a `--severity Kind=level` from the command line, which takes precedence over
+    /// the config file
+    pub fn add_severity_override(&mut self, value: &str) -> Result<()> {
+        let Some((rule, severity)) = value.split_once('=') else {
+            bail!(
+                "Expected KIND=SEVERITY, e.g. UsingHttp=error, got {}",
+                value
+            );
+        };
+        let name = rule_name(rule)?;
+        let severity = Severity::from_str(severity, true)
+            .map_err(|err| anyhow::format_err!("Invalid severity in {}: {}", value, err))?;
+        self.severity.insert(name.to_string(), severity);
+        Ok(())
+    }
+
+    /// Adds a rule to `--select`
+    pub fn add_select(&mut self, rule: &str) -> Result<()> {
+        self.select.push(rule_name(rule)?.to_string());
+        Ok(())
+    }
+
+    /// Adds a rule to `--ignore`
+    pub fn add_ignore(&mut self, rule: &str) -> Result<()> {
+        self.ignore.push(rule_name(rule)?.to_string());
+        Ok(())
+    }
+
+    pub fn severity(&self, kind: &ProblemKind) -> Severity {
+        self.severity
+            .get(kind.name())
+            .copied()
+            .unwrap_or_else(|| kind.default_severity())
+    }
+
+    /// Whether problems of the kind with this name are reported
+    pub fn is_enabled(&self, name: &str) -> bool {
+        (self.select.is_empty() || self.select.iter().any(|rule| rule == name))
+            && !self.ignore.iter().any(|rule| rule == name)
+    }
+
+    /// Whether any of the kinds is reported, so that a check producing only these kinds
+    /// can be skipped otherwise
+    pub fn any_enabled(&self, names: &[&str]) -> bool {
+        names.iter().any(|name| self.is_enabled(name))
+    }
+}
+
+/// Resolves a code such as `OPARL007` or a name such as `UsingHttp` to the name
+fn rule_name(rule: &str) -> Result<&'static str> {
+    ProblemKind::find_rule(rule)
+        .map(|(_, name)| name)
+        .with_context(|| format!("Unknown rule {}", rule))
+}
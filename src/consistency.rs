@@ -0,0 +1,177 @@
+//! Checks that the objects in the lists are the same as when fetched from their id
+//!
+//! Loading every object twice would double the runtime, so we only compare a sample of each list
+
+use crate::client::{Cache, OparlClient};
+use crate::lang::tr;
+use crate::lenient::deserialize_lenient;
+use crate::reporter::Reporter;
+use crate::visit::{OparlObject, Problem, ProblemKind};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha1_smol::Sha1;
+use std::collections::BTreeMap;
+
+/// The kinds of problems this check reports, it's skipped if they are all disabled
+pub const RULES: [&str; 1] = [ProblemKind::ListObjectDiffers("").name()];
+
+/// Collects the JSON paths where the two values differ
+///
+/// A missing key and `null` count as the same
+pub fn diff_values(left: &Value, right: &Value, path: &str, diffs: &mut Vec<String>) {
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_values(
+                    left.get(key).unwrap_or(&Value::Null),
+                    right.get(key).unwrap_or(&Value::Null),
+                    &format!("{}.{}", path, key),
+                    diffs,
+                );
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                diff_values(
+                    left.get(index).unwrap_or(&Value::Null),
+                    right.get(index).unwrap_or(&Value::Null),
+                    &format!("{}[{}]", path, index),
+                    diffs,
+                );
+            }
+        }
+        (left, right) => {
+            if left != right {
+                diffs.push(path.to_string());
+            }
+        }
+    }
+}
+
+/// The first 8 bytes of the sha1 of the id. Unlike the std hashers, sha1 is the same with every
+/// Rust version, so the sample in a checkpoint stays valid with a rebuilt binary.
+fn id_hash(id: &str) -> u64 {
+    let digest = Sha1::from(id).digest().bytes();
+    u64::from_be_bytes(digest[..8].try_into().expect("sha1 has 20 bytes"))
+}
+
+/// A deterministic sample of a list: We keep the objects whose ids have the smallest hashes,
+/// which is the same on every run and independent of the order of the list
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sample {
+    size: usize,
+    objects: BTreeMap<u64, (String, Value)>,
+}
+
+impl Sample {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            objects: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the object as it was in the list, if it belongs to the sample
+    pub fn add<T: Serialize>(&mut self, id: &str, object: &T) {
+        if self.size == 0 {
+            return;
+        }
+        let hash = id_hash(id);
+        if self.objects.len() >= self.size
+            && self
+                .objects
+                .keys()
+                .next_back()
+                .is_some_and(|max| *max <= hash)
+        {
+            return;
+        }
+        // Serialized through the schema types, so both sides are normalized the same way
+        if let Ok(value) = serde_json::to_value(object) {
+            self.objects.insert(hash, (id.to_string(), value));
+        }
+        if self.objects.len() > self.size {
+            self.objects.pop_last();
+        }
+    }
+
+    /// Fetches the sampled objects by their id and reports where they differ from the list version
+    pub async fn compare<T: DeserializeOwned + OparlObject, R: Reporter, C: Cache>(
+        self,
+        reporter: &R,
+        client: &OparlClient<C>,
+    ) {
+        for (id, list_value) in self.objects.into_values() {
+            let fetched = match client.get::<T>(&id).await {
+                Ok(fetched) => fetched,
+                Err(err) => {
+                    reporter.add_problem(Problem {
+                        detail: tr!(
+                            reporter.lang(),
+                            "Das Objekt {} aus der Liste kann nicht einzeln abgerufen werden: {}",
+                            "The object {} from the list can't be retrieved by itself: {}",
+                            id,
+                            err
+                        ),
+                        path: String::new(),
+                        kind: ProblemKind::HttpRequestFailedTooOften,
+                        url: Some(id.clone()),
+                        id: Some(id),
+                    });
+                    continue;
+                }
+            };
+            // The schema problems of the fetched version are not what we're looking for here
+            let fetched = fetched
+                .map(|fetched| (fetched, Vec::new()))
+                .or_else(|(value, _)| {
+                    deserialize_lenient(value, &id, T::type_name(), T::type_name(), reporter.lang())
+                });
+            let fetched_value = match fetched.map(|(fetched, _)| serde_json::to_value(fetched)) {
+                Ok(Ok(value)) => value,
+                Ok(Err(err)) => {
+                    reporter.add_message(&tr!(
+                        reporter.lang(),
+                        "{} konnte nicht serialisiert werden: {}",
+                        "Failed to serialize {}: {}",
+                        id,
+                        err
+                    ));
+                    continue;
+                }
+                Err((_, err)) => {
+                    reporter.add_message(&tr!(
+                        reporter.lang(),
+                        "{} konnte nicht verglichen werden: {}",
+                        "Failed to compare {}: {}",
+                        id,
+                        err
+                    ));
+                    continue;
+                }
+            };
+
+            let mut diffs = Vec::new();
+            diff_values(&list_value, &fetched_value, T::type_name(), &mut diffs);
+            // One problem per top level field, so the summary shows which fields differ
+            let mut by_field: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for diff in diffs {
+                let field = diff.split(['.', '[']).take(2).collect::<Vec<_>>().join(".");
+                by_field.entry(field).or_default().push(diff);
+            }
+            for (field, paths) in by_field {
+                reporter.add_problem(Problem {
+                    detail: format!("{}: {}", id, paths.join(", ")),
+                    path: field,
+                    kind: ProblemKind::ListObjectDiffers(T::type_name()),
+                    url: Some(id.clone()),
+                    id: Some(id.clone()),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Compares two validation runs of the same endpoint, e.g. from two days of the daily job
+//!
+//! A run is either a machine-readable report (`--format json` or `--format ndjson`) or a cache
+//! directory, which we validate again. Both are filtered by the rules of the config, so that
+//! e.g. `--ignore` works the same as for a validation
+
+use crate::cli::{ReportEntry, ReporterCli};
+use crate::client::{find_system, Cache, FileCache, NotCached, OparlClient, ResponseMetadata};
+use crate::config::Config;
+use crate::lang::{tr, Lang};
+use crate::reporter::Reporter;
+use crate::storage::Storage;
+use crate::{validate_oparl_api, ValidationOptions};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Problems are grouped by kind, oparl type and path like in [Storage::problems_grouped]
+type GroupKey = (String, Option<String>, String);
+
+/// The number of cases of each problem group in a run, with the message for the report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    groups: BTreeMap<GroupKey, (String, usize)>,
+}
+
+impl RunSummary {
+    pub fn from_entries(entries: &[ReportEntry], config: &Config) -> Self {
+        let mut groups: BTreeMap<GroupKey, (String, usize)> = BTreeMap::new();
+        for entry in entries {
+            if !config.is_enabled(&entry.kind) {
+                continue;
+            }
+            let key = (
+                entry.kind.clone(),
+                entry.type_name.clone(),
+                entry.path.clone(),
+            );
+            // Reports from older versions don't have the code
+            let message = if entry.code.is_empty() {
+                entry.message.clone()
+            } else {
+                format!("[{}] {}", entry.code, entry.message)
+            };
+            groups.entry(key).or_insert_with(|| (message, 0)).1 += 1;
+        }
+        Self { groups }
+    }
+
+    /// Reads a json or ndjson report, or validates a cache directory with the rules and the
+    /// language of the config
+    pub async fn load(path: &Path, config: &Config) -> Result<Self> {
+        let entries = if path.is_dir() {
+            validate_cache_dir(path, config.clone()).await?
+        } else {
+            read_report(path)?
+        };
+        Ok(Self::from_entries(&entries, config))
+    }
+}
+
+fn read_report(path: &Path) -> Result<Vec<ReportEntry>> {
+    let content = fs_err::read_to_string(path)?;
+    let entries = if content.trim_start().starts_with('[') {
+        serde_json::from_str(&content)
+    } else {
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect()
+    };
+    entries.with_context(|| format!("{} is not a json or ndjson report", path.display()))
+}
+
+/// Only answers from the cache, so that validating a run again doesn't download anything or
+/// change the cache directory
+struct ReadOnlyCache(FileCache);
+
+impl Cache for ReadOnlyCache {
+    fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.0.get_inner(key)
+    }
+
+    fn set_inner(&self, _key: &str, _data: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        match self.0.get(url)? {
+            Some(bytes) => Ok(Some(bytes)),
+            None => Err(NotCached(url.to_string()).into()),
+        }
+    }
+
+    fn set(&self, _url: &str, _data: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_metadata(&self, url: &str) -> Result<Option<ResponseMetadata>> {
+        self.0.get_metadata(url)
+    }
+
+    fn set_response(&self, _url: &str, _data: &[u8], _metadata: &ResponseMetadata) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Validates the cached responses again, the endpoint is taken from the cached system
+///
+/// Responses that are missing from the cache are reported as failed requests without retrying,
+/// linked objects as missing objects
+async fn validate_cache_dir(cache_dir: &Path, config: Config) -> Result<Vec<ReportEntry>> {
+    let cache = FileCache::new(cache_dir.to_path_buf())?;
+    let urls = cache.entries().into_iter().map(|entry| entry.url).collect();
+    let endpoint_url = find_system(&cache, urls)?
+        .with_context(|| format!("There is no system in {}", cache_dir.display()))?;
+    let endpoint_url = endpoint_url.as_str();
+
+    let reporter = ReporterCli::new(Storage::with_config(config), true, None);
+    let cache = cache.with_legacy_prefix(endpoint_url.trim_end_matches("system").to_string());
+    validate_oparl_api(
+        endpoint_url,
+        &reporter,
+        &OparlClient::new(ReadOnlyCache(cache)),
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let storage = reporter.get_storage();
+    let entries = storage
+        .problems_grouped()
+        .values()
+        .flatten()
+        .map(|problem| ReportEntry::new(problem, storage))
+        .collect();
+    Ok(entries)
+}
+
+/// The groups that were fixed, are new or changed their number of cases between two runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunDiff {
+    /// Message and number of cases in the old run
+    pub fixed: Vec<(String, usize)>,
+    /// Message and number of cases in the new run
+    pub new: Vec<(String, usize)>,
+    /// Message and number of cases in the old and the new run
+    pub changed: Vec<(String, usize, usize)>,
+}
+
+impl RunDiff {
+    pub fn new(old: &RunSummary, new: &RunSummary) -> Self {
+        let mut diff = Self::default();
+        for (key, (message, old_count)) in &old.groups {
+            match new.groups.get(key) {
+                None => diff.fixed.push((message.clone(), *old_count)),
+                Some((_, new_count)) if new_count != old_count => {
+                    diff.changed.push((message.clone(), *old_count, *new_count))
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, (message, new_count)) in &new.groups {
+            if !old.groups.contains_key(key) {
+                diff.new.push((message.clone(), *new_count));
+            }
+        }
+        diff
+    }
+
+    /// Writes the diff, the messages of the groups are taken from the runs as they are
+    pub fn write(&self, out: &mut impl Write, lang: Lang) -> Result<()> {
+        let header = tr!(
+            lang,
+            "=== Vergleich: {} behoben, {} neu, {} mit geänderter Anzahl ===",
+            "=== Comparison: {} fixed, {} new, {} with a changed count ===",
+            self.fixed.len(),
+            self.new.len(),
+            self.changed.len()
+        );
+        writeln!(out, "{}", header)?;
+        writeln!(out, "{}", lang.pick("== Behoben ==", "== Fixed =="))?;
+        for (message, count) in &self.fixed {
+            let count = tr!(lang, "vorher {} Fälle", "previously {} cases", count);
+            writeln!(out, "{} ({})", message, count)?;
+        }
+        writeln!(out, "{}", lang.pick("== Neu ==", "== New =="))?;
+        for (message, count) in &self.new {
+            let count = tr!(lang, "{} Fälle", "{} cases", count);
+            writeln!(out, "{} ({})", message, count)?;
+        }
+        writeln!(
+            out,
+            "{}",
+            lang.pick("== Geänderte Anzahl ==", "== Changed count ==")
+        )?;
+        for (message, old_count, new_count) in &self.changed {
+            let counts = tr!(
+                lang,
+                "{} statt vorher {} Fälle",
+                "{} instead of previously {} cases",
+                new_count,
+                old_count
+            );
+            writeln!(out, "{} ({})", message, counts)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,659 @@
+//! Longer explanations of the rules for `explain <code>`, aimed at the implementers of an endpoint
+
+use crate::lang::{tr, Lang};
+use crate::visit::ProblemKind;
+use anyhow::{Context, Result};
+use std::io::Write;
+
+pub struct Explanation {
+    pub description: &'static str,
+    /// The section of the OParl 1.1 spec with the requirement
+    pub spec_section: &'static str,
+    pub compliant: &'static str,
+    pub non_compliant: &'static str,
+    pub fix: &'static str,
+}
+
+/// The explanation for the kind with this name. The examples are the same in all languages.
+pub fn explanation(name: &str, lang: Lang) -> Option<Explanation> {
+    let explanation = match name {
+        "HttpRequestFailedTooOften" => Explanation {
+            description: lang.pick(
+                "Eine Abfrage ist auch nach mehreren Versuchen fehlgeschlagen, z.B. wegen eines Timeouts, eines Verbindungsabbruchs oder eines HTTP-Statuscodes 5xx. Die betroffene Seite oder das betroffene Objekt konnte nicht validiert werden.",
+                "A request failed even after several attempts, e.g. because of a timeout, a dropped connection or an HTTP status code 5xx. The affected page or object couldn't be validated.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Ausnahmebehandlung",
+                "Architecture: Exception handling",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper"}"#,
+            non_compliant: r#"{"error": "Internal Server Error"}"#,
+            fix: lang.pick(
+                "Prüfen Sie die Logs des Servers zu der URL aus dem Report. Lange Listen sollten so paginiert werden, dass jede Seite in wenigen Sekunden ausgeliefert werden kann.",
+                "Check the logs of the server for the url from the report. Long lists should be paginated so that every page can be delivered in a few seconds.",
+            ),
+        },
+        "HttpRequestFailedRetrying" => Explanation {
+            description: lang.pick(
+                "Eine Abfrage ist fehlgeschlagen, war aber bei einem späteren Versuch erfolgreich. Clients ohne Wiederholung würden hier abbrechen.",
+                "A request failed, but succeeded on a later attempt. Clients without retries would abort here.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Ausnahmebehandlung",
+                "Architecture: Exception handling",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper"}"#,
+            non_compliant: r#"{"error": "Service Unavailable"}"#,
+            fix: lang.pick(
+                "Prüfen Sie, ob der Server unter Last Anfragen abweist oder Timeouts zu knapp bemessen sind.",
+                "Check whether the server rejects requests under load or whether timeouts are too tight.",
+            ),
+        },
+        "ObjectMissing" => Explanation {
+            description: lang.pick(
+                "Ein Objekt wird von einem anderen Objekt verlinkt, kann aber weder in den externen Listen gefunden noch über seine URL abgerufen werden. Clients können dem Link nicht folgen.",
+                "An object is linked by another object, but can neither be found in the external lists nor be retrieved by its url. Clients can't follow the link.",
+            ),
+            spec_section: lang.pick("Architektur: URLs", "Architecture: URLs"),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "originatorPerson": ["https://example.org/person/1"]}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "originatorPerson": ["https://example.org/person/does-not-exist"]}"#,
+            fix: lang.pick(
+                "Stellen Sie sicher, dass jede verlinkte URL das Objekt ausliefert, oder entfernen Sie Links auf Objekte, die es nicht mehr gibt.",
+                "Make sure that every linked url delivers the object, or remove links to objects that don't exist anymore.",
+            ),
+        },
+        "ObjectNotInList" => Explanation {
+            description: lang.pick(
+                "Ein verlinktes Objekt kann über seine URL abgerufen werden, kommt aber in keiner externen Liste vor. Clients, die die Listen spiegeln, kennen das Objekt nicht.",
+                "A linked object can be retrieved by its url, but isn't in any external list. Clients that mirror the lists don't know the object.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/person/1", "type": "https://schema.oparl.org/1.1/Person"}], "pagination": {}, "links": {}}"#,
+            non_compliant: r#"{"data": [], "pagination": {}, "links": {}}"#,
+            fix: lang.pick(
+                "Nehmen Sie alle Objekte der Typen mit externen Listen (z.B. Person, Organization, Meeting, Paper) in die jeweilige Liste auf.",
+                "Add all objects of the types with external lists (e.g. Person, Organization, Meeting, Paper) to the respective list.",
+            ),
+        },
+        "RequiredFieldMissing" => Explanation {
+            description: lang.pick(
+                "Ein Feld, das die Spezifikation für diesen Objekttyp verpflichtend vorschreibt, fehlt.",
+                "A field that the spec makes mandatory for this object type is missing.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper"}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1"}"#,
+            fix: lang.pick(
+                "Geben Sie das Feld bei jedem Objekt aus. Für Listen ohne Inhalt wie legislativeTerm ist ein leeres Array erlaubt.",
+                "Output the field for every object. For lists without content such as legislativeTerm, an empty array is allowed.",
+            ),
+        },
+        "EmptyString" => Explanation {
+            description: lang.pick(
+                "Ein Feld ist mit einer leeren Zeichenkette angegeben. Die Spezifikation sieht vor, Felder ohne Wert wegzulassen.",
+                "A field is given as an empty string. The spec says to omit fields without a value.",
+            ),
+            spec_section: lang.pick("Architektur: JSON-Ausgabe", "Architecture: JSON output"),
+            compliant: r#"{"id": "https://example.org/agendaitem/1", "type": "https://schema.oparl.org/1.1/AgendaItem"}"#,
+            non_compliant: r#"{"id": "https://example.org/agendaitem/1", "type": "https://schema.oparl.org/1.1/AgendaItem", "number": ""}"#,
+            fix: lang.pick(
+                "Lassen Sie Felder ohne Wert weg, statt sie mit \"\" oder null auszugeben.",
+                "Omit fields without a value instead of outputting them as \"\" or null.",
+            ),
+        },
+        "UsingHttp" => Explanation {
+            description: lang.pick(
+                "Die ids verwenden http statt https. Die Spezifikation empfiehlt https, damit die Daten nicht unterwegs verändert oder mitgelesen werden können.",
+                "The ids use http instead of https. The spec recommends https, so that the data can't be modified or read in transit.",
+            ),
+            spec_section: lang.pick("Architektur: URLs", "Architecture: URLs"),
+            compliant: r#"{"id": "https://example.org/paper/1"}"#,
+            non_compliant: r#"{"id": "http://example.org/paper/1"}"#,
+            fix: lang.pick(
+                "Liefern Sie die Schnittstelle über https aus und verwenden Sie https in allen ids und Links.",
+                "Serve the interface over https and use https in all ids and links.",
+            ),
+        },
+        "InvalidJson" => Explanation {
+            description: lang.pick(
+                "Die Antwort ist kein gültiges JSON, z.B. eine HTML-Fehlerseite oder abgeschnittene Daten.",
+                "The response isn't valid JSON, e.g. an HTML error page or truncated data.",
+            ),
+            spec_section: lang.pick("Architektur: JSON-Ausgabe", "Architecture: JSON output"),
+            compliant: r#"{"id": "https://example.org/organization/1", "type": "https://schema.oparl.org/1.1/Organization"}"#,
+            non_compliant: r#"<html>Internal Server Error</html>"#,
+            fix: lang.pick(
+                "Liefern Sie auch im Fehlerfall JSON aus und prüfen Sie, ob Sonderzeichen korrekt kodiert werden.",
+                "Return JSON in the error case too and check that special characters are encoded correctly.",
+            ),
+        },
+        "WrongType" => Explanation {
+            description: lang.pick(
+                "Ein Feld hat einen anderen JSON-Datentyp als im Schema vorgesehen, z.B. eine Zahl statt einer Zeichenkette oder ein einzelner Wert statt eines Arrays. Der Wert wird bei der Validierung ignoriert.",
+                "A field has a different JSON data type than the schema specifies, e.g. a number instead of a string or a single value instead of an array. The value is ignored during the validation.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"{"id": "https://example.org/location/1", "type": "https://schema.oparl.org/1.1/Location", "description": "Rathaus"}"#,
+            non_compliant: r#"{"id": "https://example.org/location/1", "type": "https://schema.oparl.org/1.1/Location", "description": 42}"#,
+            fix: lang.pick(
+                "Geben Sie den Wert im Datentyp aus dem Schema aus. Felder mit mehreren Werten sind immer Arrays, auch mit nur einem Eintrag.",
+                "Output the value in the data type from the schema. Fields with multiple values are always arrays, even with only one entry.",
+            ),
+        },
+        "InvalidDate" => Explanation {
+            description: lang.pick(
+                "Ein Datums- oder Zeitfeld enthält keinen Wert im Format nach ISO 8601.",
+                "A date or time field doesn't contain a value in the ISO 8601 format.",
+            ),
+            spec_section: lang.pick("Architektur: Datentypen", "Architecture: Data types"),
+            compliant: r#"{"created": "2023-01-31T18:00:00+01:00", "date": "2023-01-31"}"#,
+            non_compliant: r#"{"created": "31.01.2023 18:00", "date": "2023-01-31T00:00:00+01:00"}"#,
+            fix: lang.pick(
+                "Verwenden Sie für date das Format JJJJ-MM-TT und für date-time das Format JJJJ-MM-TTThh:mm:ss mit Zeitzone.",
+                "Use the format YYYY-MM-DD for date and the format YYYY-MM-DDThh:mm:ss with a timezone for date-time.",
+            ),
+        },
+        "DateTimeMissingTimezone" => Explanation {
+            description: lang.pick(
+                "Ein Zeitpunkt ist ohne Zeitzone angegeben, damit ist er mehrdeutig.",
+                "A point in time is given without a timezone, which makes it ambiguous.",
+            ),
+            spec_section: lang.pick("Architektur: Datentypen", "Architecture: Data types"),
+            compliant: r#"{"start": "2023-01-31T18:00:00+01:00"}"#,
+            non_compliant: r#"{"start": "2023-01-31T18:00:00"}"#,
+            fix: lang.pick(
+                "Hängen Sie den Offset (z.B. +01:00) oder Z für UTC an.",
+                "Append the offset (e.g. +01:00) or Z for UTC.",
+            ),
+        },
+        "DateInsteadOfDateTime" => Explanation {
+            description: lang.pick(
+                "Ein Feld vom Typ date-time enthält nur ein Datum ohne Uhrzeit.",
+                "A field of type date-time only contains a date without a time.",
+            ),
+            spec_section: lang.pick("Architektur: Datentypen", "Architecture: Data types"),
+            compliant: r#"{"end": "2023-01-31T20:00:00+01:00"}"#,
+            non_compliant: r#"{"end": "2023-01-31"}"#,
+            fix: lang.pick(
+                "Geben Sie den Zeitpunkt mit Uhrzeit und Zeitzone aus.",
+                "Output the point in time with time and timezone.",
+            ),
+        },
+        "WrongObjectType" => Explanation {
+            description: lang.pick(
+                "Das Feld type enthält nicht die Schema-URL des erwarteten Objekttyps, z.B. wegen falscher Groß- und Kleinschreibung oder weil an der Stelle ein anderer Objekttyp steht.",
+                "The type field doesn't contain the schema url of the expected object type, e.g. because of wrong capitalization or because a different object type is in that place.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"{"id": "https://example.org/consultation/1", "type": "https://schema.oparl.org/1.1/Consultation"}"#,
+            non_compliant: r#"{"id": "https://example.org/consultation/1", "type": "https://schema.oparl.org/1.1/consultation"}"#,
+            fix: lang.pick(
+                "Verwenden Sie genau die Schema-URLs aus der Spezifikation.",
+                "Use exactly the schema urls from the spec.",
+            ),
+        },
+        "MissingBacklink" => Explanation {
+            description: lang.pick(
+                "Zwei Objekte sollten sich gegenseitig verlinken, aber das verlinkte Objekt verweist nicht zurück, z.B. eine Mitgliedschaft auf eine Gruppierung, die die Mitgliedschaft nicht enthält.",
+                "Two objects should link each other, but the linked object doesn't link back, e.g. a membership to an organization that doesn't contain the membership.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"[{"id": "https://example.org/membership/1", "organization": "https://example.org/organization/1"}, {"id": "https://example.org/organization/1", "membership": ["https://example.org/membership/1"]}]"#,
+            non_compliant: r#"[{"id": "https://example.org/membership/1", "organization": "https://example.org/organization/1"}, {"id": "https://example.org/organization/1", "membership": []}]"#,
+            fix: lang.pick(
+                "Erzeugen Sie beide Richtungen der Verknüpfung aus denselben Daten.",
+                "Generate both directions of the link from the same data.",
+            ),
+        },
+        "PaginationTotalElements" => Explanation {
+            description: lang.pick(
+                "pagination.totalElements stimmt nicht mit der Anzahl der Objekte in der Liste überein.",
+                "pagination.totalElements doesn't match the number of objects in the list.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/1"}], "pagination": {"totalElements": 1}, "links": {}}"#,
+            non_compliant: r#"{"data": [{"id": "https://example.org/paper/1"}], "pagination": {"totalElements": 2}, "links": {}}"#,
+            fix: lang.pick(
+                "Zählen Sie mit denselben Bedingungen wie bei der Ausgabe der Liste, oder lassen Sie das optionale Feld weg.",
+                "Count with the same conditions as when outputting the list, or omit the optional field.",
+            ),
+        },
+        "PaginationTotalPages" => Explanation {
+            description: lang.pick(
+                "pagination.totalPages stimmt nicht mit der Anzahl der Seiten der Liste überein.",
+                "pagination.totalPages doesn't match the number of pages of the list.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [], "pagination": {"totalPages": 1, "currentPage": 1}, "links": {}}"#,
+            non_compliant: r#"{"data": [], "pagination": {"totalPages": 5, "currentPage": 1}, "links": {}}"#,
+            fix: lang.pick(
+                "Berechnen Sie die Seitenzahl aus der Gesamtzahl und der Seitengröße, oder lassen Sie das optionale Feld weg.",
+                "Calculate the number of pages from the total count and the page size, or omit the optional field.",
+            ),
+        },
+        "PaginationCurrentPage" => Explanation {
+            description: lang.pick(
+                "pagination.currentPage steigt beim Folgen der next-Links nicht um eins.",
+                "pagination.currentPage doesn't increase by one when following the next links.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [], "pagination": {"currentPage": 2}, "links": {"next": "https://example.org/paper?page=3"}}"#,
+            non_compliant: r#"{"data": [], "pagination": {"currentPage": 1}, "links": {"next": "https://example.org/paper?page=3"}}"#,
+            fix: lang.pick(
+                "Geben Sie die Nummer der ausgelieferten Seite an, beginnend mit 1.",
+                "Give the number of the delivered page, starting with 1.",
+            ),
+        },
+        "PaginationElementsPerPage" => Explanation {
+            description: lang.pick(
+                "Eine Seite enthält mehr Objekte als in pagination.elementsPerPage angegeben, oder eine Seite vor der letzten enthält weniger.",
+                "A page contains more objects than given in pagination.elementsPerPage, or a page before the last one contains fewer.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/1"}], "pagination": {"elementsPerPage": 1}, "links": {}}"#,
+            non_compliant: r#"{"data": [{"id": "https://example.org/paper/1"}, {"id": "https://example.org/paper/2"}], "pagination": {"elementsPerPage": 1}, "links": {}}"#,
+            fix: lang.pick(
+                "Geben Sie die tatsächliche Seitengröße an.",
+                "Give the actual page size.",
+            ),
+        },
+        "ListLinkMissing" => Explanation {
+            description: lang.pick(
+                "Ein Link der Paginierung wie links.first, links.self oder links.next fehlt, obwohl er benötigt wird.",
+                "A pagination link such as links.first, links.self or links.next is missing even though it's needed.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [], "pagination": {}, "links": {"first": "https://example.org/paper", "self": "https://example.org/paper", "next": "https://example.org/paper?page=2"}}"#,
+            non_compliant: r#"{"data": [], "pagination": {}, "links": {}}"#,
+            fix: lang.pick(
+                "Geben Sie die Links auf jeder Seite aus, next auf allen Seiten außer der letzten.",
+                "Output the links on every page, next on all pages except the last one.",
+            ),
+        },
+        "ListLinkWrong" => Explanation {
+            description: lang.pick(
+                "Ein Link der Paginierung zeigt auf eine andere Seite als erwartet, z.B. links.self nicht auf die aktuelle Seite.",
+                "A pagination link points to a different page than expected, e.g. links.self not to the current page.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [], "pagination": {}, "links": {"self": "https://example.org/paper?page=2"}}"#,
+            non_compliant: r#"{"data": [], "pagination": {}, "links": {"self": "https://example.org/paper"}}"#,
+            fix: lang.pick(
+                "Erzeugen Sie die Links aus den Parametern der aktuellen Anfrage.",
+                "Generate the links from the parameters of the current request.",
+            ),
+        },
+        "PaginationLoop" => Explanation {
+            description: lang.pick(
+                "Der next-Link einer Seite verweist auf eine Seite, die schon abgerufen wurde. Clients, die allen next-Links folgen, hören nie auf.",
+                "The next link of a page points to a page that was already retrieved. Clients that follow all next links never stop.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [], "pagination": {}, "links": {"self": "https://example.org/paper?page=2"}}"#,
+            non_compliant: r#"{"data": [], "pagination": {}, "links": {"self": "https://example.org/paper?page=2", "next": "https://example.org/paper"}}"#,
+            fix: lang.pick(
+                "Lassen Sie links.next auf der letzten Seite weg.",
+                "Omit links.next on the last page.",
+            ),
+        },
+        "DuplicateObjectInList" => Explanation {
+            description: lang.pick(
+                "Ein Objekt kommt mehrfach in derselben externen Liste vor, meist weil sich die Sortierung während der Paginierung ändert.",
+                "An object occurs several times in the same external list, usually because the order changes during the pagination.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/1"}, {"id": "https://example.org/paper/2"}], "pagination": {}, "links": {}}"#,
+            non_compliant: r#"{"data": [{"id": "https://example.org/paper/1"}, {"id": "https://example.org/paper/1"}], "pagination": {}, "links": {}}"#,
+            fix: lang.pick(
+                "Sortieren Sie die Liste nach einem eindeutigen, unveränderlichen Schlüssel.",
+                "Sort the list by a unique, immutable key.",
+            ),
+        },
+        "FilterRequestFailed" => Explanation {
+            description: lang.pick(
+                "Die Liste kann mit einem der Filter created_since, created_until, modified_since, modified_until oder limit nicht abgerufen werden.",
+                "The list can't be retrieved with one of the filters created_since, created_until, modified_since, modified_until or limit.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Filter für externe Objektlisten",
+                "Architecture: Filters for external object lists",
+            ),
+            compliant: r#"{"data": [], "pagination": {}, "links": {"self": "https://example.org/paper?modified_since=2023-01-01T00:00:00%2B01:00"}}"#,
+            non_compliant: r#"{"error": "Unknown parameter modified_since"}"#,
+            fix: lang.pick(
+                "Unterstützen Sie alle Filter der Spezifikation, mit Zeitpunkten im Format date-time.",
+                "Support all filters of the spec, with points in time in the date-time format.",
+            ),
+        },
+        "FilterWrongObjects" => Explanation {
+            description: lang.pick(
+                "Eine gefilterte Liste enthält Objekte, deren Zeitstempel nicht zum Filter passen.",
+                "A filtered list contains objects whose timestamps don't match the filter.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Filter für externe Objektlisten",
+                "Architecture: Filters for external object lists",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/2", "modified": "2023-02-01T00:00:00+01:00"}], "pagination": {}, "links": {"self": "https://example.org/paper?modified_since=2023-01-15T00:00:00%2B01:00"}}"#,
+            non_compliant: r#"{"data": [{"id": "https://example.org/paper/1", "modified": "2023-01-01T00:00:00+01:00"}], "pagination": {}, "links": {"self": "https://example.org/paper?modified_since=2023-01-15T00:00:00%2B01:00"}}"#,
+            fix: lang.pick(
+                "Wenden Sie den Filter auf dasselbe Feld an, das ausgegeben wird, und beachten Sie die Zeitzone.",
+                "Apply the filter to the same field that is output, and mind the timezone.",
+            ),
+        },
+        "FilterMissingObjects" => Explanation {
+            description: lang.pick(
+                "Einer gefilterten Liste fehlen Objekte, die zum Filter passen. Bei modified_since müssen auch gelöschte Objekte enthalten sein, damit Clients Löschungen mitbekommen.",
+                "A filtered list is missing objects that match the filter. With modified_since, deleted objects must be included too, so that clients notice deletions.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Filter für externe Objektlisten",
+                "Architecture: Filters for external object lists",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/2", "modified": "2023-02-01T00:00:00+01:00", "deleted": true}], "pagination": {}, "links": {}}"#,
+            non_compliant: r#"{"data": [], "pagination": {}, "links": {}}"#,
+            fix: lang.pick(
+                "Filtern Sie nur nach dem Zeitstempel und nehmen Sie gelöschte Objekte in die Ergebnisse von modified_since auf.",
+                "Filter only by the timestamp and include deleted objects in the results of modified_since.",
+            ),
+        },
+        "FilterLimitIgnored" => Explanation {
+            description: lang.pick(
+                "Die Liste liefert mehr Objekte pro Seite als mit dem Parameter limit angefordert.",
+                "The list returns more objects per page than requested with the limit parameter.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Filter für externe Objektlisten",
+                "Architecture: Filters for external object lists",
+            ),
+            compliant: r#"{"data": [{"id": "https://example.org/paper/1"}, {"id": "https://example.org/paper/2"}], "pagination": {}, "links": {"self": "https://example.org/paper?limit=2"}}"#,
+            non_compliant: r#"{"data": [{"id": "https://example.org/paper/1"}, {"id": "https://example.org/paper/2"}, {"id": "https://example.org/paper/3"}], "pagination": {}, "links": {"self": "https://example.org/paper?limit=2"}}"#,
+            fix: lang.pick(
+                "Begrenzen Sie die Seitengröße auf limit und übernehmen Sie den Parameter in die Links der Paginierung.",
+                "Limit the page size to limit and keep the parameter in the pagination links.",
+            ),
+        },
+        "BrokenAccessUrl" => Explanation {
+            description: lang.pick(
+                "Die Datei unter accessUrl kann nicht abgerufen werden. accessUrl ist das einzige Pflichtfeld einer Datei außer id und type.",
+                "The file at accessUrl can't be retrieved. accessUrl is the only mandatory field of a file besides id and type.",
+            ),
+            spec_section: lang.pick("Schema: oparl:File", "Schema: oparl:File"),
+            compliant: r#"{"id": "https://example.org/file/1", "type": "https://schema.oparl.org/1.1/File", "accessUrl": "https://example.org/file/1.pdf"}"#,
+            non_compliant: r#"{"id": "https://example.org/file/1", "type": "https://schema.oparl.org/1.1/File", "accessUrl": "https://intranet.example.org/file/1.pdf"}"#,
+            fix: lang.pick(
+                "Verwenden Sie öffentlich erreichbare URLs und prüfen Sie, ob die Dateien noch auf dem Server liegen.",
+                "Use publicly reachable urls and check whether the files are still on the server.",
+            ),
+        },
+        "BrokenDownloadUrl" => Explanation {
+            description: lang.pick(
+                "Die Datei unter downloadUrl kann nicht abgerufen werden.",
+                "The file at downloadUrl can't be retrieved.",
+            ),
+            spec_section: lang.pick("Schema: oparl:File", "Schema: oparl:File"),
+            compliant: r#"{"id": "https://example.org/file/1", "downloadUrl": "https://example.org/file/1.pdf?download=1"}"#,
+            non_compliant: r#"{"id": "https://example.org/file/1", "downloadUrl": "https://example.org/file/removed.pdf"}"#,
+            fix: lang.pick(
+                "Verwenden Sie öffentlich erreichbare URLs oder lassen Sie das optionale Feld weg.",
+                "Use publicly reachable urls or omit the optional field.",
+            ),
+        },
+        "DeadWebPage" => Explanation {
+            description: lang.pick(
+                "Die Webseite unter web oder website ist nicht erreichbar.",
+                "The web page at web or website isn't reachable.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"{"id": "https://example.org/body/1", "website": "https://example.org"}"#,
+            non_compliant: r#"{"id": "https://example.org/body/1", "website": "https://old.example.org"}"#,
+            fix: lang.pick(
+                "Aktualisieren Sie die URL oder lassen Sie das optionale Feld weg.",
+                "Update the url or omit the optional field.",
+            ),
+        },
+        "UnreachableLicense" => Explanation {
+            description: lang.pick(
+                "Die Lizenz unter license oder fileLicense ist nicht erreichbar, damit ist für Nachnutzer unklar, unter welchen Bedingungen sie die Daten verwenden dürfen.",
+                "The license at license or fileLicense isn't reachable, so it's unclear to reusers under which conditions they may use the data.",
+            ),
+            spec_section: lang.pick(
+                "Schema: oparl:System, oparl:Body und oparl:File",
+                "Schema: oparl:System, oparl:Body and oparl:File",
+            ),
+            compliant: r#"{"id": "https://example.org/body/1", "license": "https://creativecommons.org/licenses/by/4.0/"}"#,
+            non_compliant: r#"{"id": "https://example.org/body/1", "license": "https://example.org/lizenz-alt"}"#,
+            fix: lang.pick(
+                "Verlinken Sie eine dauerhaft erreichbare Lizenz-URL.",
+                "Link a permanently reachable license url.",
+            ),
+        },
+        "ListObjectDiffers" => Explanation {
+            description: lang.pick(
+                "Ein Objekt hat in der externen Liste einen anderen Inhalt als beim Abruf über seine id.",
+                "An object has a different content in the external list than when retrieved by its id.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Objektlisten und Paginierung",
+                "Architecture: Object lists and pagination",
+            ),
+            compliant: r#"[{"id": "https://example.org/paper/1", "name": "Haushalt 2023"}, {"id": "https://example.org/paper/1", "name": "Haushalt 2023"}]"#,
+            non_compliant: r#"[{"id": "https://example.org/paper/1", "name": "Haushalt 2023"}, {"id": "https://example.org/paper/1", "name": "Haushalt 2024"}]"#,
+            fix: lang.pick(
+                "Erzeugen Sie die Objekte in Listen und Einzelabrufen mit demselben Code, und prüfen Sie Caches auf veraltete Einträge.",
+                "Generate the objects in lists and single requests with the same code, and check caches for stale entries.",
+            ),
+        },
+        "DeletedObjectHasPayload" => Explanation {
+            description: lang.pick(
+                "Ein gelöschtes Objekt enthält noch Felder außer id, type, created, modified und deleted.",
+                "A deleted object still contains fields other than id, type, created, modified and deleted.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Gelöschte Objekte",
+                "Architecture: Deleted objects",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "created": "2023-01-01T00:00:00+01:00", "modified": "2023-02-01T00:00:00+01:00", "deleted": true}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "name": "Haushalt 2023", "modified": "2023-02-01T00:00:00+01:00", "deleted": true}"#,
+            fix: lang.pick(
+                "Geben Sie bei gelöschten Objekten nur die erlaubten Felder aus.",
+                "Output only the allowed fields for deleted objects.",
+            ),
+        },
+        "DeletedObjectWithoutModified" => Explanation {
+            description: lang.pick(
+                "Einem gelöschten Objekt fehlt modified. Ohne den Zeitpunkt der Löschung können Clients mit modified_since die Löschung nicht erkennen.",
+                "A deleted object is missing modified. Without the time of the deletion, clients using modified_since can't notice the deletion.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Gelöschte Objekte",
+                "Architecture: Deleted objects",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "modified": "2023-02-01T00:00:00+01:00", "deleted": true}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "type": "https://schema.oparl.org/1.1/Paper", "deleted": true}"#,
+            fix: lang.pick(
+                "Setzen Sie modified beim Löschen auf den Zeitpunkt der Löschung.",
+                "Set modified to the time of the deletion when deleting.",
+            ),
+        },
+        "LinkToDeletedObject" => Explanation {
+            description: lang.pick(
+                "Ein nicht gelöschtes Objekt verlinkt ein gelöschtes Objekt.",
+                "An object that isn't deleted links a deleted object.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Gelöschte Objekte",
+                "Architecture: Deleted objects",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "relatedPaper": []}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "relatedPaper": ["https://example.org/paper/2"]}"#,
+            fix: lang.pick(
+                "Entfernen Sie beim Löschen eines Objekts auch die Links darauf und aktualisieren Sie modified der verlinkenden Objekte.",
+                "When deleting an object, also remove the links to it and update modified of the linking objects.",
+            ),
+        },
+        "VendorField" => Explanation {
+            description: lang.pick(
+                "Ein Feld außerhalb der Spezifikation mit einem Präfix wie ris:. Solche herstellerspezifischen Erweiterungen sind erlaubt, werden von Clients aber nicht ausgewertet.",
+                "A field outside of the spec with a prefix such as ris:. Such vendor-specific extensions are allowed, but aren't evaluated by clients.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Herstellerspezifische Erweiterungen",
+                "Architecture: Vendor-specific extensions",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "ris:status": "offen"}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "status": "offen"}"#,
+            fix: lang.pick(
+                "Nichts zu tun, sofern die Information nicht in ein Feld der Spezifikation gehört.",
+                "Nothing to do, unless the information belongs in a field of the spec.",
+            ),
+        },
+        "MisspelledField" => Explanation {
+            description: lang.pick(
+                "Ein Feld außerhalb der Spezifikation, das einem Feld der Spezifikation sehr ähnlich ist, z.B. modfied statt modified oder agendaitem statt agendaItem. Clients finden den Wert nicht.",
+                "A field outside of the spec that is very similar to a field of the spec, e.g. modfied instead of modified or agendaitem instead of agendaItem. Clients don't find the value.",
+            ),
+            spec_section: lang.pick(
+                "Schema: die Beschreibung des jeweiligen Objekttyps",
+                "Schema: the description of the respective object type",
+            ),
+            compliant: r#"{"id": "https://example.org/paper/1", "modified": "2023-02-01T00:00:00+01:00"}"#,
+            non_compliant: r#"{"id": "https://example.org/paper/1", "modfied": "2023-02-01T00:00:00+01:00"}"#,
+            fix: lang.pick(
+                "Verwenden Sie die Schreibweise aus der Spezifikation, Groß- und Kleinschreibung beachten.",
+                "Use the spelling from the spec, including capitalization.",
+            ),
+        },
+        "UnknownField" => Explanation {
+            description: lang.pick(
+                "Ein Feld, das weder in der Spezifikation vorkommt noch mit einem Präfix als herstellerspezifische Erweiterung gekennzeichnet ist.",
+                "A field that neither occurs in the spec nor is marked as a vendor-specific extension with a prefix.",
+            ),
+            spec_section: lang.pick(
+                "Architektur: Herstellerspezifische Erweiterungen",
+                "Architecture: Vendor-specific extensions",
+            ),
+            compliant: r#"{"id": "https://example.org/file/1", "ris:body": "https://example.org/body/1"}"#,
+            non_compliant: r#"{"id": "https://example.org/file/1", "body": "https://example.org/body/1"}"#,
+            fix: lang.pick(
+                "Entfernen Sie das Feld oder versehen Sie es mit einem Präfix für Ihr System.",
+                "Remove the field or give it a prefix for your system.",
+            ),
+        },
+        "NoCacheValidators" => Explanation {
+            description: lang.pick(
+                "Die Antwort hat weder den Header ETag noch Last-Modified. Clients können daher nicht nachfragen, ob sich die Antwort geändert hat, und müssen jedes Mal alles neu laden.",
+                "The response has neither an ETag nor a Last-Modified header. Clients therefore can't ask whether the response has changed and have to load everything again every time.",
+            ),
+            spec_section: lang.pick(
+                "RFC 9110, Abschnitt 8.8: Validatoren",
+                "RFC 9110, section 8.8: Validators",
+            ),
+            compliant: r#"{"status": 200, "headers": {"ETag": "\"5f3a-1\"", "Last-Modified": "Wed, 01 Feb 2023 08:00:00 GMT"}}"#,
+            non_compliant: r#"{"status": 200, "headers": {"Content-Type": "application/json"}}"#,
+            fix: lang.pick(
+                "Senden Sie ein ETag, z.B. einen Hash der Antwort, oder Last-Modified mit dem größten modified der enthaltenen Objekte.",
+                "Send an ETag, e.g. a hash of the response, or Last-Modified with the largest modified of the contained objects.",
+            ),
+        },
+        "ConditionalRequestIgnored" => Explanation {
+            description: lang.pick(
+                "Der Server hat auf eine Abfrage mit If-None-Match oder If-Modified-Since die unveränderte Antwort vollständig gesendet statt mit 304 Not Modified zu antworten. Das kostet bei jeder Aktualisierung unnötig Zeit und Bandbreite.",
+                "The server sent the unchanged response in full for a request with If-None-Match or If-Modified-Since instead of answering with 304 Not Modified. That costs unnecessary time and bandwidth on every update.",
+            ),
+            spec_section: lang.pick(
+                "RFC 9110, Abschnitt 13.1: Bedingte Abfragen",
+                "RFC 9110, section 13.1: Conditional requests",
+            ),
+            compliant: r#"{"request": {"If-None-Match": "\"5f3a-1\""}, "status": 304}"#,
+            non_compliant: r#"{"request": {"If-None-Match": "\"5f3a-1\""}, "status": 200, "headers": {"ETag": "\"5f3a-1\""}}"#,
+            fix: lang.pick(
+                "Vergleichen Sie If-None-Match mit dem aktuellen ETag bzw. If-Modified-Since mit Last-Modified und antworten Sie bei Gleichheit mit 304 ohne Inhalt.",
+                "Compare If-None-Match with the current ETag or If-Modified-Since with Last-Modified and answer with 304 without content if they are equal.",
+            ),
+        },
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+/// Writes the explanation for a rule given by code or name
+pub fn write_explanation(rule: &str, lang: Lang, out: &mut impl Write) -> Result<()> {
+    let (code, name) = ProblemKind::find_rule(rule).with_context(|| {
+        tr!(
+            lang,
+            "Unbekannte Regel {}, die Codes gehen von {} bis {}",
+            "Unknown rule {}, the codes go from {} to {}",
+            rule,
+            ProblemKind::RULES[0].0,
+            ProblemKind::RULES[ProblemKind::RULES.len() - 1].0
+        )
+    })?;
+    let explanation = explanation(name, lang).context("Every rule has an explanation")?;
+    writeln!(out, "{} {}", code, name)?;
+    writeln!(out)?;
+    writeln!(out, "{}", explanation.description)?;
+    writeln!(out)?;
+    let spec = tr!(
+        lang,
+        "Spezifikation: {}",
+        "Spec: {}",
+        explanation.spec_section
+    );
+    writeln!(out, "{}", spec)?;
+    writeln!(out)?;
+    writeln!(out, "{}", lang.pick("Richtig:", "Correct:"))?;
+    writeln!(out, "{}", explanation.compliant)?;
+    writeln!(out)?;
+    writeln!(out, "{}", lang.pick("Falsch:", "Incorrect:"))?;
+    writeln!(out, "{}", explanation.non_compliant)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{}",
+        tr!(lang, "Behebung: {}", "Fix: {}", explanation.fix)
+    )?;
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! Checks that the external lists support the filters required by the spec
+//!
+//! We rerun each list with `created_since`, `created_until`, `modified_since`, `modified_until`
+//! and `limit`, using timestamps of the objects we've seen in the unfiltered list. The server
+//! has to return exactly the matching objects, and for `modified_since` also the deleted ones.
+
+use crate::client::{Cache, OparlClient};
+use crate::lang::{tr, Lang};
+use crate::reporter::Reporter;
+use crate::storage::ListObject;
+use crate::visit::{Problem, ProblemKind};
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset};
+use reqwest::Url;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// The kinds of problems this check reports, it's skipped if they are all disabled
+pub const RULES: [&str; 4] = [
+    ProblemKind::FilterRequestFailed("").name(),
+    ProblemKind::FilterWrongObjects("").name(),
+    ProblemKind::FilterMissingObjects("").name(),
+    ProblemKind::FilterLimitIgnored.name(),
+];
+
+/// The number of objects we request with `limit`
+const LIMIT: usize = 2;
+
+/// The timestamp field with the filters for after and before that timestamp
+pub(crate) const FILTERS: [(&str, &str, &str); 2] = [
+    ("created", "created_since", "created_until"),
+    ("modified", "modified_since", "modified_until"),
+];
+
+pub(crate) fn with_query(url: &str, key: &str, value: &str) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    url.query_pairs_mut().append_pair(key, value);
+    Ok(url.to_string())
+}
+
+fn parse_time(value: Option<&str>) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value?).ok()
+}
+
+fn timestamp<'a>(object: &'a ListObject, field: &str) -> Option<&'a str> {
+    match field {
+        "created" => object.created.as_deref(),
+        _ => object.modified.as_deref(),
+    }
+}
+
+/// Reads the fields we need from an object in a filtered list
+fn list_object(value: &Value) -> Option<ListObject> {
+    let string = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+    Some(ListObject {
+        id: string("id")?,
+        created: string("created"),
+        modified: string("modified"),
+        deleted: value
+            .get("deleted")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+/// Loads the objects of a filtered list, either all pages or only the first one
+///
+/// The schema of the list is already checked in the unfiltered run, so we only read
+/// `data` and `links.next`
+async fn get_filtered<C: Cache>(
+    client: &OparlClient<C>,
+    url: &str,
+    all_pages: bool,
+    lang: Lang,
+) -> Result<Vec<ListObject>, String> {
+    let mut objects = Vec::new();
+    let mut visited_pages = HashSet::new();
+    let mut next_url = Some(url.to_string());
+    while let Some(current_url) = next_url {
+        if !visited_pages.insert(current_url.clone()) {
+            break;
+        }
+        let page: Value = match client.get(&current_url).await {
+            Ok(Ok(page)) => page,
+            Ok(Err((_, err))) => return Err(err),
+            Err(err) => return Err(err.to_string()),
+        };
+        let data = page.get("data").and_then(Value::as_array).ok_or_else(|| {
+            tr!(
+                lang,
+                "{} enthält keine Liste in data",
+                "{} contains no list in data",
+                current_url
+            )
+        })?;
+        objects.extend(data.iter().filter_map(list_object));
+        if !all_pages {
+            break;
+        }
+        next_url = page
+            .get("links")
+            .and_then(|links| links.get("next"))
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+    }
+    Ok(objects)
+}
+
+/// Reruns the list at `list_url` with each filter and compares the result with the unfiltered list
+pub async fn check_filters<R: Reporter, C: Cache>(
+    type_name: &'static str,
+    list_url: &str,
+    reporter: &R,
+    client: &OparlClient<C>,
+) -> Result<()> {
+    let objects = reporter
+        .get_storage()
+        .list_objects
+        .lock()
+        .get(list_url)
+        .cloned()
+        .unwrap_or_default();
+    let lang = reporter.lang();
+    let add_problem = |kind: ProblemKind, url: &str, detail: String, id: Option<&str>| {
+        reporter.add_problem(Problem {
+            detail: format!("{}: {}", url, detail),
+            path: type_name.to_string(),
+            kind,
+            url: Some(url.to_string()),
+            id: id.map(ToString::to_string),
+        })
+    };
+
+    for (field, since, until) in FILTERS {
+        let mut times: Vec<(DateTime<FixedOffset>, &str)> = objects
+            .iter()
+            .filter_map(|object| {
+                let value = timestamp(object, field)?;
+                Some((parse_time(Some(value))?, value))
+            })
+            .collect();
+        if times.is_empty() {
+            continue;
+        }
+        // The median splits the list in two halves, so both filters have something to do
+        times.sort();
+        let (threshold, threshold_value) = times[times.len() / 2];
+
+        for (filter, is_since) in [(since, true), (until, false)] {
+            let matches = |time: DateTime<FixedOffset>| {
+                if is_since {
+                    time >= threshold
+                } else {
+                    time <= threshold
+                }
+            };
+            let url = match with_query(list_url, filter, threshold_value) {
+                Ok(url) => url,
+                Err(err) => {
+                    add_problem(
+                        ProblemKind::FilterRequestFailed(filter),
+                        list_url,
+                        err.to_string(),
+                        None,
+                    );
+                    continue;
+                }
+            };
+            let filtered = match get_filtered(client, &url, true, lang).await {
+                Ok(filtered) => filtered,
+                Err(err) => {
+                    add_problem(ProblemKind::FilterRequestFailed(filter), &url, err, None);
+                    continue;
+                }
+            };
+
+            // Objects with exactly the threshold are left out since servers may store the
+            // timestamps with a higher precision than they return
+            let filtered_ids: HashSet<&str> =
+                filtered.iter().map(|object| object.id.as_str()).collect();
+            let missing: Vec<&ListObject> = objects
+                .iter()
+                .filter(|object| {
+                    parse_time(timestamp(object, field))
+                        .is_some_and(|time| time != threshold && matches(time))
+                        && !filtered_ids.contains(object.id.as_str())
+                })
+                .collect();
+            if let Some(example) = missing.first() {
+                let deleted = missing.iter().filter(|object| object.deleted).count();
+                let mut detail = tr!(
+                    lang,
+                    "{} passende Objekte fehlen, z.B. {}",
+                    "{} matching objects are missing, e.g. {}",
+                    missing.len(),
+                    example.id
+                );
+                if deleted > 0 {
+                    detail += &tr!(
+                        lang,
+                        ", darunter {} gelöschte",
+                        ", including {} deleted",
+                        deleted
+                    );
+                }
+                add_problem(
+                    ProblemKind::FilterMissingObjects(filter),
+                    &url,
+                    detail,
+                    Some(&example.id),
+                );
+            }
+
+            // We use the timestamps from the filtered list since objects may have been
+            // modified in the meantime
+            let wrong: Vec<&ListObject> = filtered
+                .iter()
+                .filter(|object| {
+                    parse_time(timestamp(object, field))
+                        .is_some_and(|time| time != threshold && !matches(time))
+                })
+                .collect();
+            if let Some(example) = wrong.first() {
+                add_problem(
+                    ProblemKind::FilterWrongObjects(filter),
+                    &url,
+                    tr!(
+                        lang,
+                        "{} Objekte passen nicht zum Filter, z.B. {} mit {} {}",
+                        "{} objects don't match the filter, e.g. {} with {} {}",
+                        wrong.len(),
+                        example.id,
+                        field,
+                        timestamp(example, field).unwrap_or_default()
+                    ),
+                    Some(&example.id),
+                );
+            }
+        }
+    }
+
+    if objects.len() > LIMIT {
+        let url = match with_query(list_url, "limit", &LIMIT.to_string()) {
+            Ok(url) => url,
+            Err(err) => {
+                let kind = ProblemKind::FilterRequestFailed("limit");
+                add_problem(kind, list_url, err.to_string(), None);
+                return Ok(());
+            }
+        };
+        match get_filtered(client, &url, false, lang).await {
+            Ok(first_page) if first_page.len() > LIMIT => add_problem(
+                ProblemKind::FilterLimitIgnored,
+                &url,
+                tr!(
+                    lang,
+                    "{} statt höchstens {} Objekte auf der ersten Seite",
+                    "{} instead of at most {} objects on the first page",
+                    first_page.len(),
+                    LIMIT
+                ),
+                None,
+            ),
+            Ok(_) => {}
+            Err(err) => add_problem(ProblemKind::FilterRequestFailed("limit"), &url, err, None),
+        }
+    }
+    Ok(())
+}
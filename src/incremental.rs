@@ -0,0 +1,101 @@
+//! The object index for `--incremental`, so that nightly runs only load what changed
+//!
+//! After each run we store all objects of the external lists with the time the run started.
+//! The next run only loads the lists with `modified_since` set to that time, replaces the
+//! changed objects in the index and validates the merged objects as if it had loaded the
+//! whole lists.
+//!
+//! The start time is taken from our clock, which may be ahead of the server's clock, and the
+//! server may set `modified` to when a change started rather than when it was committed. So
+//! we store the start time minus [SINCE_OVERLAP]: objects changed shortly before the run are
+//! loaded again by the next one, which is cheap, instead of being missed.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// The name of the index in the cache directory
+pub const INDEX_FILE: &str = "incremental.json";
+
+/// How far the `modified_since` of the next run goes back before the start of this run
+pub const SINCE_OVERLAP: Duration = Duration::from_secs(60 * 60);
+
+/// An object from an external list as the server returned it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IndexedObject {
+    /// The page the object was on, for the urls in the problems
+    pub page_url: String,
+    pub object: Value,
+}
+
+impl IndexedObject {
+    pub fn id(&self) -> Option<&str> {
+        self.object.get("id").and_then(Value::as_str)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectIndex {
+    /// When the run that wrote the index started minus [SINCE_OVERLAP], the next run loads the
+    /// objects modified since
+    pub since: String,
+    /// The objects of each external list by the url of the list, in the order of the list
+    pub lists: HashMap<String, Vec<IndexedObject>>,
+}
+
+impl ObjectIndex {
+    /// Returns `None` if there's no index yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let index = serde_json::from_str(&fs_err::read_to_string(path)?)
+            .with_context(|| format!("Invalid index {}", path.display()))?;
+        Ok(Some(index))
+    }
+
+    /// Writes to a temporary file first, so a crash while writing keeps the last index
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let temp_path = path.with_extension("json.tmp");
+        let mut writer = BufWriter::new(fs_err::File::create(&temp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        drop(writer);
+        fs_err::rename(temp_path, path)?;
+        Ok(())
+    }
+}
+
+/// The current time minus [SINCE_OVERLAP] in the format of the `modified_since` filter
+pub fn since_now() -> String {
+    DateTime::<Utc>::from(SystemTime::now() - SINCE_OVERLAP)
+        .to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Replaces the objects that changed since the last run and appends the new ones
+pub fn merge(previous: &[IndexedObject], changed: Vec<IndexedObject>) -> Vec<IndexedObject> {
+    let mut changed_by_id: HashMap<String, IndexedObject> = changed
+        .iter()
+        .filter_map(|object| Some((object.id()?.to_string(), object.clone())))
+        .collect();
+    let mut merged: Vec<IndexedObject> = previous
+        .iter()
+        .map(|object| {
+            object
+                .id()
+                .and_then(|id| changed_by_id.remove(id))
+                .unwrap_or_else(|| object.clone())
+        })
+        .collect();
+    merged.extend(
+        changed
+            .into_iter()
+            .filter(|object| object.id().is_some_and(|id| changed_by_id.contains_key(id))),
+    );
+    merged
+}
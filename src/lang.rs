@@ -0,0 +1,40 @@
+//! The language of all messages, problem details and reports
+//!
+//! Texts are written inline in both languages with [tr], so the catalogue stays next to the
+//! place where the text is used. To add a language, add a variant and the compiler shows every
+//! text that needs a translation.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    De,
+    En,
+}
+
+impl Lang {
+    /// Picks the text for this language
+    pub fn pick<T>(self, de: T, en: T) -> T {
+        match self {
+            Lang::De => de,
+            Lang::En => en,
+        }
+    }
+}
+
+/// Formats the german or the english text, e.g.
+/// `tr!(lang, "{} Fälle", "{} cases", count)`. Exported for the messages of the binary.
+#[macro_export]
+macro_rules! tr {
+    ($lang:expr, $de:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match $lang {
+            $crate::lang::Lang::De => format!($de $(, $arg)*),
+            $crate::lang::Lang::En => format!($en $(, $arg)*),
+        }
+    };
+}
+
+pub use tr;
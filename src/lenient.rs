@@ -0,0 +1,170 @@
+//! Tolerant decoding: Instead of rejecting a whole list page because of a single wrongly typed
+//! field, we remove the offending value, report it and try again
+
+use crate::external_list::ExternalList;
+use crate::lang::Lang;
+use crate::visit::{format_detail, Problem, ProblemKind};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+
+/// Deserializes `value`, dropping each value that doesn't match the schema.
+///
+/// `root_name` is used for paths outside of `data` (e.g. `ExternalList` or `System`), while
+/// `item_name` is used for the objects in `data`. On success, returns the decoded object and a
+/// [ProblemKind::WrongType] for each removed value. If the value can't be decoded even after
+/// removing fields (e.g. because `data` is missing), returns the error that can't be fixed.
+/// The details of the problems are in `lang`.
+pub fn deserialize_lenient<T: DeserializeOwned>(
+    mut value: Value,
+    url: &str,
+    root_name: &str,
+    item_name: &str,
+    lang: Lang,
+) -> Result<(T, Vec<Problem>), (Value, String)> {
+    let mut problems = Vec::new();
+    match drop_wrong_values(&mut value, url, root_name, item_name, lang, &mut problems) {
+        Ok(data) => Ok((data, problems)),
+        Err(error) => Err((value, error)),
+    }
+}
+
+/// Like [deserialize_lenient] for a page of an external list, but fixes each object in `data`
+/// on its own. Each removed value means parsing again, which would otherwise be the whole page
+/// each time. Runs on a blocking thread like [crate::client::OparlClient::get].
+pub async fn deserialize_list_lenient<T: DeserializeOwned + Send + 'static>(
+    value: Value,
+    url: &str,
+    item_name: &'static str,
+    lang: Lang,
+) -> Result<(ExternalList<T>, Vec<Problem>), (Value, String)> {
+    let url = url.to_string();
+    let deserialize = move || deserialize_list_items(value, &url, item_name, lang);
+
+    #[cfg(target_family = "wasm")]
+    let data = deserialize();
+    #[cfg(not(target_family = "wasm"))]
+    let data = tokio::task::spawn_blocking(deserialize).await.unwrap();
+
+    data
+}
+
+fn deserialize_list_items<T: DeserializeOwned>(
+    mut value: Value,
+    url: &str,
+    item_name: &str,
+    lang: Lang,
+) -> Result<(ExternalList<T>, Vec<Problem>), (Value, String)> {
+    let mut problems = Vec::new();
+    if let Some(Value::Array(items)) = value.get_mut("data") {
+        for item in items {
+            // Objects that can't be fixed on their own are left to the whole page below
+            let _ = drop_wrong_values::<T>(item, url, item_name, item_name, lang, &mut problems);
+        }
+    }
+    match drop_wrong_values(
+        &mut value,
+        url,
+        "ExternalList",
+        item_name,
+        lang,
+        &mut problems,
+    ) {
+        Ok(data) => Ok((data, problems)),
+        Err(error) => Err((value, error)),
+    }
+}
+
+/// Removes wrongly typed values from `value` until it can be decoded, adding a problem for
+/// each, or returns the error that can't be fixed
+fn drop_wrong_values<T: DeserializeOwned>(
+    value: &mut Value,
+    url: &str,
+    root_name: &str,
+    item_name: &str,
+    lang: Lang,
+    problems: &mut Vec<Problem>,
+) -> Result<T, String> {
+    loop {
+        let error = match serde_path_to_error::deserialize(&*value) {
+            Ok(data) => return Ok(data),
+            Err(error) => error,
+        };
+        let id = object_id(value, error.path()).map(ToString::to_string);
+        if !remove_value(value, error.path()) {
+            return Err(error.to_string());
+        }
+        problems.push(Problem {
+            detail: format!(
+                "{}: {} ({})",
+                format_detail(id.as_deref(), url, lang),
+                error.inner(),
+                error.path()
+            ),
+            path: problem_path(error.path(), root_name, item_name),
+            kind: ProblemKind::WrongType,
+            url: Some(url.to_string()),
+            id,
+        });
+    }
+}
+
+/// Turns `data[3].mainFile.size` into `Paper.mainFile.size` so that the problems can be grouped
+fn problem_path(path: &Path, root_name: &str, item_name: &str) -> String {
+    let segments: Vec<&Segment> = path.iter().collect();
+    let (name, rest) = match segments.as_slice() {
+        [Segment::Map { key }, Segment::Seq { .. }, rest @ ..] if key == "data" => {
+            (item_name, rest)
+        }
+        rest => (root_name, rest),
+    };
+    let mut problem_path = name.to_string();
+    for segment in rest {
+        if let Segment::Map { key } = segment {
+            problem_path.push('.');
+            problem_path.push_str(key);
+        }
+    }
+    problem_path
+}
+
+/// The id of the object containing the broken value, either in `data` or at the root
+fn object_id<'a>(value: &'a Value, path: &Path) -> Option<&'a str> {
+    let object = match path.iter().collect::<Vec<_>>().as_slice() {
+        [Segment::Map { key }, Segment::Seq { index }, ..] if key == "data" => {
+            value.get("data")?.get(index)?
+        }
+        _ => value,
+    };
+    object.get("id")?.as_str()
+}
+
+/// Removes the value at `path`, returning false if there's nothing that could be removed
+fn remove_value(value: &mut Value, path: &Path) -> bool {
+    let segments: Vec<&Segment> = path.iter().collect();
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut parent = value;
+    for segment in parents {
+        let child = match (segment, parent) {
+            (Segment::Map { key }, Value::Object(map)) => map.get_mut(key),
+            (Segment::Seq { index }, Value::Array(array)) => array.get_mut(*index),
+            _ => None,
+        };
+        match child {
+            Some(child) => parent = child,
+            None => return false,
+        }
+    }
+
+    match (last, parent) {
+        (Segment::Map { key }, Value::Object(map)) => map.remove(key).is_some(),
+        (Segment::Seq { index }, Value::Array(array)) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
@@ -1,26 +1,53 @@
+use crate::checkpoint::{Checkpoint, ListProgress, CHECKPOINT_INTERVAL};
+use crate::incremental::{IndexedObject, ObjectIndex};
 use crate::reporter::Reporter;
 use crate::semaphore::Semaphore;
 use crate::visit::Visitable;
 use anyhow::{Context, Error, Result};
-use client::{Cache, OparlClient};
+use clap::Args;
+use client::{Cache, NotCached, OparlClient, Revalidation};
 use external_list::ExternalList;
+use filters::{check_filters, with_query};
 use futures::prelude::stream::FuturesUnordered;
 use futures::StreamExt;
+use lenient::{deserialize_lenient, deserialize_list_lenient};
+use links::check_links;
 use reporter::ProgressBarWrapper;
-use schema::{Body, Meeting, Organization, Paper, Person, System};
+use reqwest::Url;
+use schema::{
+    AgendaItem, Body, Consultation, File, LegislativeTerm, Location, Meeting, Membership,
+    Organization, Paper, Person, System,
+};
 use serde::de::DeserializeOwned;
-use std::collections::HashMap;
-#[cfg(not(target_family = "wasm"))]
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::Duration;
+use storage::{FieldLinks, ListObject};
 #[cfg(not(target_family = "wasm"))]
 use tokio::time::sleep;
 use visit::{OparlObject, Problem, ProblemKind};
 
+#[cfg(not(target_family = "wasm"))]
+pub mod archive;
+pub mod baseline;
 pub mod batch;
+pub mod cache_dir;
+pub mod checkpoint;
 pub mod cli;
 pub mod client;
+pub mod config;
+pub mod consistency;
+pub mod diff;
 pub mod endpoints;
+pub mod explain;
 pub mod external_list;
+pub mod filters;
+pub mod incremental;
+pub mod lang;
+pub mod lenient;
+pub mod links;
+pub mod pagination;
 pub mod reporter;
 pub mod schema;
 pub mod semaphore;
@@ -28,36 +55,234 @@ pub mod storage;
 pub mod visit;
 pub mod wasm;
 
+/// Optional checks that need additional requests to the server, resuming from a checkpoint and
+/// incremental runs
+#[derive(Args, Clone, Debug, Default)]
+pub struct ValidationOptions {
+    /// Rerun the lists with created_since, modified_until, limit etc. and check the results
+    #[arg(long)]
+    pub check_filters: bool,
+    /// Check that the files, web pages and licenses linked from the objects can be loaded
+    #[arg(long)]
+    pub check_links: bool,
+    /// Fetch this many objects of each list by their id and compare them with the list version
+    #[arg(long, default_value_t = 0)]
+    pub compare_sample: usize,
+    /// Continue an interrupted validation from the checkpoint in the cache directory
+    #[arg(long)]
+    pub resume: bool,
+    /// Where the progress is saved regularly for `--resume`, set for `--cache`
+    #[arg(skip)]
+    pub checkpoint: Option<PathBuf>,
+    /// The minimum time between two checkpoints while going through the lists, zero writes one
+    /// after every page
+    #[arg(skip = CHECKPOINT_INTERVAL)]
+    pub checkpoint_interval: Duration,
+    /// Only load the objects that changed since the last run and validate them together with
+    /// the unchanged objects from the last run
+    #[arg(long)]
+    pub incremental: bool,
+    /// The objects of the last run for `--incremental`, set for `--cache`
+    #[arg(skip)]
+    pub index: Option<PathBuf>,
+    /// Ask the server whether cached responses changed, with ETag and Last-Modified
+    #[arg(long)]
+    pub revalidate: bool,
+    /// Load cached responses again if they are older than this, e.g. `7d` or `12h`
+    #[arg(long, value_name = "AGE", value_parser = cache_dir::parse_age)]
+    pub cache_max_age: Option<Duration>,
+}
+
+/// Reports the responses that can't be revalidated and the servers that ignore conditional
+/// requests, once per host with the number of responses and an example, since a server
+/// usually behaves the same for all of them
+pub fn report_revalidations<C: Cache>(client: &OparlClient<C>, reporter: &impl Reporter) {
+    let revalidations = client.take_revalidations();
+    let not_modified = revalidations
+        .iter()
+        .filter(|(_, revalidation)| *revalidation == Revalidation::NotModified)
+        .count();
+    reporter.add_message(&tr!(
+        reporter.lang(),
+        "{} von {} Antworten im Cache waren unverändert",
+        "{} of {} cached responses were unchanged",
+        not_modified,
+        revalidations.len()
+    ));
+    // Sorted, so that the problems and the examples are the same in each run
+    let mut by_host: BTreeMap<(ProblemKind, String), (usize, String)> = BTreeMap::new();
+    for (url, revalidation) in revalidations {
+        let kind = match revalidation {
+            Revalidation::NoValidators => ProblemKind::NoCacheValidators,
+            Revalidation::Ignored => ProblemKind::ConditionalRequestIgnored,
+            Revalidation::NotModified | Revalidation::Modified => continue,
+        };
+        let host = Url::parse(&url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(ToString::to_string))
+            .unwrap_or_else(|| url.clone());
+        let (count, example) = by_host.entry((kind, host)).or_insert((0, url.clone()));
+        *count += 1;
+        if url < *example {
+            *example = url;
+        }
+    }
+    for ((kind, host), (count, example)) in by_host {
+        reporter.add_problem(Problem {
+            detail: tr!(
+                reporter.lang(),
+                "{} Antworten von {}, z.B. {}",
+                "{} responses from {}, e.g. {}",
+                count,
+                host,
+                example
+            ),
+            path: "".to_string(),
+            kind,
+            url: Some(example),
+            id: None,
+        });
+    }
+}
+
+/// Loads a page of a list. With `raw`, the page is also returned as plain json for the index of
+/// `--incremental`, since the typed objects lose unknown and invalid fields.
+async fn get_page<T: DeserializeOwned + OparlObject, C: Cache>(
+    client: &OparlClient<C>,
+    url: &str,
+    raw: bool,
+) -> Result<(Result<ExternalList<T>, (Value, String)>, Option<Value>)> {
+    if !raw {
+        return Ok((client.get(url).await?, None));
+    }
+    // Any valid json is a `Value`
+    let value = match client.get::<Value>(url).await? {
+        Ok(value) | Err((value, _)) => value,
+    };
+    let page =
+        serde_json::from_value(value.clone()).map_err(|err| (value.clone(), err.to_string()));
+    Ok((page, Some(value)))
+}
+
+/// Adds the problems found during lenient decoding and returns the decoded value
+fn lenient_problems<T>(reporter: &impl Reporter, (data, problems): (T, Vec<Problem>)) -> T {
+    for problem in problems {
+        reporter.add_problem(problem);
+    }
+    data
+}
+
 /// Go through an external list with objects of type T
+///
+/// The position in the list is kept in the storage, so that it is part of the checkpoints and
+/// a resumed validation continues after the last page of the checkpoint.
+///
+/// If the list is in the index of the last run, we only go through the objects modified since
+/// then and take the other objects from the index.
 async fn process_list<T: DeserializeOwned + OparlObject, R: Reporter, C: Cache>(
     first_url: &str,
     reporter: &R,
     client: &OparlClient<C>,
     progress_bar: R::ProgressBar,
+    options: &ValidationOptions,
+    index: Option<&ObjectIndex>,
 ) -> Result<()> {
-    let mut next_url = Some(first_url.to_string());
-    let mut object_count = 0;
-    let mut page_count = 0;
+    let storage = reporter.get_storage();
+    if storage.is_completed(first_url) {
+        progress_bar.finish_with_message(tr!(
+            reporter.lang(),
+            "{} aus dem Checkpoint übernommen",
+            "{} taken from the checkpoint",
+            T::type_name()
+        ));
+        return Ok(());
+    }
+    let sample_size = if storage.config.any_enabled(&consistency::RULES) {
+        options.compare_sample
+    } else {
+        0
+    };
+    let mut previous = index.and_then(|index| Some((&index.since, index.lists.get(first_url)?)));
+    let start_url = match previous.map(|(since, _)| with_query(first_url, "modified_since", since))
+    {
+        Some(Ok(url)) => url,
+        // Loading the list fails the same way and is reported there, so we treat it like a
+        // list that's not in the index yet
+        Some(Err(_)) => {
+            previous = None;
+            first_url.to_string()
+        }
+        None => first_url.to_string(),
+    };
+    let resumed_pages = storage
+        .list_progress
+        .lock()
+        .entry(first_url.to_string())
+        .or_insert_with(|| ListProgress::new(&start_url, sample_size, reporter.lang()))
+        .page_count;
+    progress_bar.inc(resumed_pages as u64);
 
     // If we have next page url, load it, else exit
-    while let Some(current_url) = next_url {
+    loop {
+        // Between two pages, so that the checkpoint matches the position in the list
+        if let Some(checkpoint) = &options.checkpoint {
+            if Checkpoint::is_due(storage, options.checkpoint_interval) {
+                Checkpoint::save(storage, checkpoint).await?;
+            }
+        }
+        let current_url = {
+            let mut list_progress = storage.list_progress.lock();
+            let progress = list_progress.get_mut(first_url).expect("Added above");
+            let Some(current_url) = progress.next_url.clone() else {
+                break;
+            };
+            // A next link pointing to an earlier page would otherwise make us loop forever
+            if progress.visited_pages.contains(&current_url) {
+                reporter.add_problem(Problem {
+                    detail: tr!(
+                        reporter.lang(),
+                        "Die Liste {} verweist nach {} Seiten mit links.next wieder auf die bereits abgerufene Seite {}",
+                        "After {1} pages, the list {0} points with links.next back to the already retrieved page {2}",
+                        first_url,
+                        progress.page_count,
+                        current_url
+                    ),
+                    path: "ExternalList.links.next".to_string(),
+                    kind: ProblemKind::PaginationLoop,
+                    url: Some(current_url.clone()),
+                    id: None,
+                });
+                progress.pagination.page_failed();
+                progress.next_url = None;
+                break;
+            }
+            current_url
+        };
         progress_bar.set_message(current_url.to_string());
-        // Very naive retry implementation
+        // Very naive retry implementation. The failed attempts are only reported together with
+        // the page, so that a checkpoint in between doesn't get them twice on resume. Answers
+        // that won't change aren't retried.
         let mut counter = 0;
-        let page: Result<ExternalList<T>, _> = loop {
-            match client.get(&current_url).await {
+        let mut retries = Vec::new();
+        let (page, raw_page) = loop {
+            match get_page::<T, C>(client, &current_url, options.incremental).await {
                 Ok(page) => break page,
                 Err(err) => {
                     counter += 1;
 
-                    if counter < 3 {
-                        reporter.add_problem(Problem {
-                            detail: format!(
+                    if counter < 3 && !is_definite(&err) {
+                        retries.push(Problem {
+                            detail: tr!(
+                                reporter.lang(),
                                 "Abfrage von {} ist fehlgeschlagen, wird erneut versucht: {}",
-                                current_url, err
+                                "Request to {} failed, retrying: {}",
+                                current_url,
+                                err
                             ),
                             path: "".to_string(),
                             kind: ProblemKind::HttpRequestFailedRetrying,
+                            url: Some(current_url.clone()),
+                            id: None,
                         });
                         // TODO(konstin): Also wait when using wasm
                         #[cfg(not(target_family = "wasm"))]
@@ -66,45 +291,69 @@ async fn process_list<T: DeserializeOwned + OparlObject, R: Reporter, C: Cache>(
                         }
                         continue;
                     } else {
+                        for problem in retries {
+                            reporter.add_problem(problem);
+                        }
                         reporter.add_problem(Problem {
-                            detail: format!(
+                            detail: tr!(
+                                reporter.lang(),
                                 "Die Abfrage {} ist zu oft fehlgeschlagen, eine Validierung der Liste ist nicht möglich: {}",
-                                current_url, err
+                                "The request {} failed too often, the list can't be validated: {}",
+                                current_url,
+                                err
                             ),
                             path: "".to_string(),
                             kind: ProblemKind::HttpRequestFailedTooOften,
+                            url: Some(current_url.clone()),
+                            id: None,
                         });
                         // The page failed to often, we can't do anything with that list anymore
+                        storage.list_progress.lock().remove(first_url);
+                        storage.complete(first_url);
                         return Ok(());
                     }
                 }
             }
         };
+        // If the page matches the schema, take it, otherwise drop the wrongly typed fields and
+        // if even that fails try to at least extract the next url
+        let page = match page {
+            Ok(page) => Ok((page, Vec::new())),
+            Err((value, _)) => {
+                deserialize_list_lenient(value, &current_url, T::type_name(), reporter.lang()).await
+            }
+        };
 
-        page_count += 1;
+        // From here on until the next page, nothing awaits, so a checkpoint written by another
+        // list can't see the page half done
+        let mut list_progress = storage.list_progress.lock();
+        let progress = list_progress.get_mut(first_url).expect("Added above");
+        progress.page_count += 1;
+        progress.visited_pages.insert(current_url.clone());
+        for problem in retries {
+            reporter.add_problem(problem);
+        }
 
-        // If the page matches the schema, take it, otherwise try to at least extract the next url
         let page = match page {
-            Ok(page) => {
-                next_url = page.links.next.to_owned();
-                if let Some(total_pages) = page.pagination.total_pages {
-                    progress_bar.set_length(total_pages as u64);
-                }
-
-                page
-            }
+            Ok(page) => page,
             Err((value, error)) => {
                 reporter.add_problem(Problem {
-                    detail: format!(
+                    detail: tr!(
+                        reporter.lang(),
                         "Die Liste unter {} passt nicht zum Schema von externen Listen: {}",
-                        current_url, error
+                        "The list at {} doesn't match the schema of external lists: {}",
+                        current_url,
+                        error
                     ),
                     path: String::new(),
                     kind: ProblemKind::InvalidJson,
+                    url: Some(current_url.clone()),
+                    id: None,
                 });
+                progress.pagination.page_failed();
                 // Try to extract $.links.next so that we can continue with the next page even
                 // if this one failed
-                next_url = value
+                progress.next_url = value
                     .as_object()
                     .and_then(|x| x.get("links"))
                     .and_then(|x| x.as_object())
@@ -114,119 +363,591 @@ async fn process_list<T: DeserializeOwned + OparlObject, R: Reporter, C: Cache>(
                 continue;
             }
         };
+        let page = lenient_problems(reporter, page);
+        for problem in progress.pagination.check_page(&page, &current_url) {
+            reporter.add_problem(problem);
+        }
+
+        progress.next_url = page.links.next.to_owned();
+        if let Some(total_pages) = page.pagination.total_pages {
+            progress_bar.set_length(total_pages as u64);
+        }
+
+        let raw_objects = raw_page
+            .as_ref()
+            .and_then(|page| page.get("data"))
+            .and_then(Value::as_array);
+        let mut indexed_ids = HashSet::new();
+        for object in raw_objects.into_iter().flatten() {
+            let Some(id) = object.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            if !progress.id_counts.contains_key(id) && indexed_ids.insert(id) {
+                progress.objects.push(IndexedObject {
+                    page_url: current_url.clone(),
+                    object: object.clone(),
+                });
+            }
+        }
 
         // Actual page logic
-        object_count += page.data.len();
+        progress.object_count += page.data.len();
         for object in page.data {
-            object.visit(reporter, &current_url, object.get_id(), &current_url);
+            if let Some(id) = object.get_id() {
+                let count = progress.id_counts.entry(id.to_string()).or_default();
+                *count += 1;
+                // We already validated this object on an earlier page
+                if *count > 1 {
+                    continue;
+                }
+                storage.add_list_object(
+                    first_url,
+                    ListObject {
+                        id: id.to_string(),
+                        created: object.get_created().map(ToString::to_string),
+                        modified: object.get_modified().map(ToString::to_string),
+                        deleted: object.get_deleted(),
+                    },
+                );
+                progress.sample.add(id, &object);
+            }
+            object.visit(reporter, &current_url, object.get_id(), "");
         }
         progress_bar.inc(1);
         reporter.finish_page();
     }
 
-    progress_bar.finish_with_message(format!(
-        "Found {} {} in {} pages",
-        object_count,
-        T::type_name(),
-        page_count
-    ));
+    let mut progress = storage
+        .list_progress
+        .lock()
+        .remove(first_url)
+        .expect("Added above");
+    let unchanged = match previous {
+        Some((_, previous)) => {
+            visit_unchanged::<T, R>(first_url, previous, reporter, &mut progress)
+        }
+        None => 0,
+    };
+    if options.incremental {
+        let objects = match previous {
+            Some((_, previous)) => incremental::merge(previous, progress.objects),
+            None => progress.objects,
+        };
+        storage
+            .indexed_objects
+            .lock()
+            .insert(first_url.to_string(), objects);
+    }
+    for problem in progress.pagination.finish() {
+        reporter.add_problem(problem);
+    }
+    for (id, count) in progress.id_counts {
+        if count > 1 {
+            reporter.add_problem(Problem {
+                detail: tr!(
+                    reporter.lang(),
+                    "{} kommt {} mal in {} vor",
+                    "{} occurs {} times in {}",
+                    id,
+                    count,
+                    first_url
+                ),
+                path: String::new(),
+                kind: ProblemKind::DuplicateObjectInList(T::type_name()),
+                url: Some(first_url.to_string()),
+                id: Some(id),
+            });
+        }
+    }
+    // Before the comparison, which awaits, so that a checkpoint never contains the list
+    // without its progress. A crash during the comparison loses the problems it found so far.
+    storage.complete(first_url);
+
+    progress.sample.compare::<T, _, _>(reporter, client).await;
+
+    if previous.is_some() {
+        progress_bar.finish_with_message(tr!(
+            reporter.lang(),
+            "{} geänderte {} auf {} Seiten gefunden, {} unverändert aus dem letzten Lauf",
+            "Found {} changed {} in {} pages, {} unchanged from the last run",
+            progress.object_count,
+            T::type_name(),
+            progress.page_count,
+            unchanged
+        ));
+    } else {
+        progress_bar.finish_with_message(tr!(
+            reporter.lang(),
+            "{} {} auf {} Seiten gefunden",
+            "Found {} {} in {} pages",
+            progress.object_count,
+            T::type_name(),
+            progress.page_count
+        ));
+    }
     Ok(())
 }
 
-/// Check whether the urls that were not in the list can be loaded individually
-#[allow(clippy::await_holding_lock)] // For this function it doesn't matter anymore
-async fn analyze_missing_urls<P: Reporter, C: Cache>(
+/// Validates the objects from the index of the last run that didn't change since then,
+/// returning how many there were
+fn visit_unchanged<T: DeserializeOwned + OparlObject, R: Reporter>(
+    list_url: &str,
+    previous: &[IndexedObject],
+    reporter: &R,
+    progress: &mut ListProgress,
+) -> usize {
+    let storage = reporter.get_storage();
+    let mut unchanged = 0;
+    for indexed in previous {
+        let Some(id) = indexed.id() else {
+            continue;
+        };
+        if progress.id_counts.contains_key(id) {
+            continue;
+        }
+        unchanged += 1;
+        let page_url = &indexed.page_url;
+        let object = match serde_json::from_value::<T>(indexed.object.clone())
+            .map(|object| (object, Vec::new()))
+            .or_else(|_| {
+                deserialize_lenient(
+                    indexed.object.clone(),
+                    page_url,
+                    T::type_name(),
+                    T::type_name(),
+                    reporter.lang(),
+                )
+            }) {
+            Ok(object) => lenient_problems(reporter, object),
+            Err((_, error)) => {
+                reporter.add_problem(Problem {
+                    detail: tr!(
+                        reporter.lang(),
+                        "Das Objekt {} passt nicht zum Schema von {}: {}",
+                        "The object {} doesn't match the schema of {}: {}",
+                        id,
+                        T::type_name(),
+                        error
+                    ),
+                    path: String::new(),
+                    kind: ProblemKind::InvalidJson,
+                    url: Some(page_url.clone()),
+                    id: Some(id.to_string()),
+                });
+                continue;
+            }
+        };
+        storage.add_list_object(
+            list_url,
+            ListObject {
+                id: id.to_string(),
+                created: object.get_created().map(ToString::to_string),
+                modified: object.get_modified().map(ToString::to_string),
+                deleted: object.get_deleted(),
+            },
+        );
+        progress.sample.add(id, &object);
+        object.visit(reporter, page_url, object.get_id(), "");
+    }
+    unchanged
+}
+
+/// Loads an object that was linked but not in any list and validates it like the list objects
+async fn fetch_object<T: DeserializeOwned + OparlObject, R: Reporter, C: Cache>(
     client: &OparlClient<C>,
-    reporter: &P,
+    reporter: &R,
+    url: &str,
 ) -> Result<()> {
+    let object = client.get::<T>(url).await?;
+    let object = match object
+        .map(|object| (object, Vec::new()))
+        .or_else(|(value, _)| {
+            deserialize_lenient(value, url, T::type_name(), T::type_name(), reporter.lang())
+        }) {
+        Ok(object) => lenient_problems(reporter, object),
+        Err((_, error)) => {
+            // The object exists, it's just broken
+            reporter.add_problem(Problem {
+                detail: tr!(
+                    reporter.lang(),
+                    "Das Objekt unter {} passt nicht zum Schema von {}: {}",
+                    "The object at {} doesn't match the schema of {}: {}",
+                    url,
+                    T::type_name(),
+                    error
+                ),
+                path: String::new(),
+                kind: ProblemKind::InvalidJson,
+                url: Some(url.to_string()),
+                id: Some(url.to_string()),
+            });
+            return Ok(());
+        }
+    };
+    object.visit(reporter, url, object.get_id(), "");
+    Ok(())
+}
+
+/// The result of loading an object that wasn't in the lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Linked {
+    Loaded,
+    /// The server answered that there's no such object, which we remember for the next runs
+    Missing,
+    /// The request failed even after retrying, e.g. because of a timeout or a 5xx, so we
+    /// don't know whether the object exists
+    Failed,
+}
+
+/// Whether the error is an answer that won't change when asking again: the object doesn't
+/// exist (404, 410), the response isn't json or it isn't in a read-only cache
+fn is_definite(err: &Error) -> bool {
+    err.chain().any(|cause| {
+        let status = cause
+            .downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status);
+        matches!(
+            status,
+            Some(reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE)
+        ) || cause.is::<serde_json::Error>()
+            || cause.is::<NotCached>()
+    })
+}
+
+/// Loads and validates an object of the type `type_name`, retrying like the list pages
+async fn fetch_linked<R: Reporter, C: Cache>(
+    client: &OparlClient<C>,
+    reporter: &R,
+    semaphore: &Semaphore,
+    type_name: &'static str,
+    url: &str,
+) -> (String, Linked) {
+    let _permit = semaphore.acquire().await.unwrap();
+    let mut counter = 0;
+    let linked = loop {
+        let result = match type_name {
+            "AgendaItem" => fetch_object::<AgendaItem, _, _>(client, reporter, url).await,
+            "Body" => fetch_object::<Body, _, _>(client, reporter, url).await,
+            "Consultation" => fetch_object::<Consultation, _, _>(client, reporter, url).await,
+            "File" => fetch_object::<File, _, _>(client, reporter, url).await,
+            "LegislativeTerm" => fetch_object::<LegislativeTerm, _, _>(client, reporter, url).await,
+            "Location" => fetch_object::<Location, _, _>(client, reporter, url).await,
+            "Meeting" => fetch_object::<Meeting, _, _>(client, reporter, url).await,
+            "Membership" => fetch_object::<Membership, _, _>(client, reporter, url).await,
+            "Organization" => fetch_object::<Organization, _, _>(client, reporter, url).await,
+            "Paper" => fetch_object::<Paper, _, _>(client, reporter, url).await,
+            "Person" => fetch_object::<Person, _, _>(client, reporter, url).await,
+            "System" => fetch_object::<System, _, _>(client, reporter, url).await,
+            _ => unreachable!("{} is not an oparl type", type_name),
+        };
+        let err = match result {
+            Ok(()) => break Linked::Loaded,
+            Err(err) => err,
+        };
+        if is_definite(&err) {
+            reporter.add_message(&tr!(
+                reporter.lang(),
+                "Fehler beim Abruf von {}: {}",
+                "Error in GET request to {}: {}",
+                url,
+                err
+            ));
+            break Linked::Missing;
+        }
+        counter += 1;
+        if counter < 3 {
+            reporter.add_problem(Problem {
+                detail: tr!(
+                    reporter.lang(),
+                    "Abfrage von {} ist fehlgeschlagen, wird erneut versucht: {}",
+                    "Request to {} failed, retrying: {}",
+                    url,
+                    err
+                ),
+                path: "".to_string(),
+                kind: ProblemKind::HttpRequestFailedRetrying,
+                url: Some(url.to_string()),
+                id: None,
+            });
+            // TODO(konstin): Also wait when using wasm
+            #[cfg(not(target_family = "wasm"))]
+            {
+                sleep(Duration::from_secs(5)).await;
+            }
+        } else {
+            reporter.add_problem(Problem {
+                detail: tr!(
+                    reporter.lang(),
+                    "Die Abfrage {} ist zu oft fehlgeschlagen: {}",
+                    "The request {} failed too often: {}",
+                    url,
+                    err
+                ),
+                path: "".to_string(),
+                kind: ProblemKind::HttpRequestFailedTooOften,
+                url: Some(url.to_string()),
+                id: Some(url.to_string()),
+            });
+            break Linked::Failed;
+        }
+    };
+    (url.to_string(), linked)
+}
+
+/// The linked urls that are not the id of an object we've seen
+fn get_missing_urls(reporter: &impl Reporter) -> Vec<(&'static str, String)> {
     let known_ids = reporter.get_storage().known_ids.lock();
-    let linked_ids = reporter.get_storage().linked_ids.lock();
-    let missing_urls: Vec<_> = linked_ids
+    reporter
+        .get_storage()
+        .linked_ids
+        .lock()
         .keys()
-        .map(|(_, url)| url)
-        .filter(|url| !known_ids.contains(url.as_str()))
-        .collect();
+        .filter(|(_, url)| !known_ids.contains(url.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// The name of the file with the urls of the missing objects that couldn't be loaded, in the
+/// cache directory
+pub const MISSING_IDS_FILE: &str = "missing_ids.json";
+
+/// Load the urls that were not in the list individually and validate them
+///
+/// The loaded objects can link further objects that are missing, so we repeat until
+/// there's nothing new. Urls the server answered with 404 or 410 or not with json are stored
+/// in `missing_ids.json`, the successful responses are in the normal cache. Requests that
+/// failed otherwise, e.g. with a timeout, are tried again in the next run. A `true` in
+/// `missing_ids.json` is from older versions which only sent a HEAD request, those objects
+/// are counted as reachable but not validated.
+async fn analyze_missing_urls<P: Reporter, C: Cache>(
+    client: &OparlClient<C>,
+    reporter: &P,
+) -> Result<()> {
+    // Those are the ones that weren't in the lists
+    let missing_urls = get_missing_urls(reporter);
+    if missing_urls.is_empty() {
+        // TODO: Report this on wasm
+        println!(
+            "{}",
+            reporter
+                .lang()
+                .pick("Keine fehlenden Objekte", "No missing objects")
+        );
+        return Ok(());
+    }
 
     let mut known_statuses: HashMap<String, bool> =
-        if let Some(bytes) = client.cache.get_inner("missing_ids.json")? {
+        if let Some(bytes) = client.cache.get_inner(MISSING_IDS_FILE)? {
             serde_json::from_slice(&bytes).context("missing_ids.json is corrupted")?
         } else {
             HashMap::new()
         };
 
-    /// Check whether the url is reachable, as a function due to typechecker limitations
-    async fn head(semaphore: &Semaphore, url: &str, reporter: &impl Reporter) -> (String, bool) {
-        let _permit = semaphore.acquire().await.unwrap();
-        let status = match reqwest::Client::new().head(url).send().await {
-            Ok(response) => response.status().is_success(),
-            Err(err) => {
-                reporter.add_message(&format!("Error in HEAD request to {}: {}", url, err));
-                false
-            }
-        };
-        (url.to_string(), status)
-    }
-
     let semaphore = Semaphore::new(50);
-    let mut futures: FuturesUnordered<_> = missing_urls
-        .iter()
-        .filter(|url| !known_statuses.contains_key(&url.to_string()))
-        .map(|url| head(&semaphore, url, reporter)) // .boxed()
-        .collect();
-
-    if futures.is_empty() && known_statuses.is_empty() {
-        // TODO: Report this on wasm
-        println!("No missing objects");
-        return Ok(());
-    }
-
-    if !futures.is_empty() {
-        let bar = reporter.add_bar("Fehlende Objekte überprüfen");
-        bar.set_length(futures.len() as u64);
-        while let Some((url, status)) = futures.next().await {
+    let bar = reporter.add_bar(
+        reporter
+            .lang()
+            .pick("Fehlende Objekte abrufen", "Fetching missing objects"),
+    );
+    let mut total = 0;
+    // All urls that weren't in the lists, including those linked by the loaded objects
+    let mut not_in_list: HashSet<String> = HashSet::new();
+    let mut new_urls = missing_urls;
+    let mut statuses_changed = false;
+    // Already reported as failed requests, we don't know whether they exist
+    let mut failed: HashSet<String> = HashSet::new();
+    while !new_urls.is_empty() {
+        let to_fetch: Vec<_> = new_urls
+            .into_iter()
+            .filter(|(_, url)| not_in_list.insert(url.clone()))
+            .filter(|(_, url)| !known_statuses.contains_key(url))
+            .collect();
+        total += to_fetch.len();
+        bar.set_length(total as u64);
+        let mut futures: FuturesUnordered<_> = to_fetch
+            .iter()
+            .map(|(type_name, url)| fetch_linked(client, reporter, &semaphore, type_name, url))
+            .collect();
+        while let Some((url, linked)) = futures.next().await {
             bar.inc(1);
-            known_statuses.insert(url, status);
+            match linked {
+                Linked::Loaded => {}
+                Linked::Missing => {
+                    known_statuses.insert(url, false);
+                    statuses_changed = true;
+                }
+                Linked::Failed => {
+                    failed.insert(url);
+                }
+            }
         }
+        drop(futures);
 
-        let count = linked_ids
-            .iter()
-            .filter(|((_type_name, url), _sources)| missing_urls.contains(&url))
-            .count();
-
-        bar.finish_with_message(format!("{} fehlende Objekte gefunden", count));
+        // Links from the objects we just loaded
+        new_urls = get_missing_urls(reporter)
+            .into_iter()
+            .filter(|(_, url)| !not_in_list.contains(url))
+            .collect();
+    }
+    bar.finish_with_message(tr!(
+        reporter.lang(),
+        "{} fehlende Objekte abgerufen",
+        "Fetched {} missing objects",
+        total
+    ));
 
+    if statuses_changed {
         client
             .cache
-            .set_inner("missing_ids.json", &serde_json::to_vec(&known_statuses)?)?;
+            .set_inner(MISSING_IDS_FILE, &serde_json::to_vec(&known_statuses)?)?;
     }
 
-    for ((type_name, url), sources) in linked_ids.iter() {
-        if !missing_urls.contains(&url) {
+    let mut problems = Vec::new();
+    for ((type_name, url), sources) in reporter.get_storage().linked_ids.lock().iter() {
+        if !not_in_list.contains(url) || failed.contains(url) {
             continue;
         }
-        reporter.add_problem(Problem {
-            detail: format!(
+        // Only the missing objects are stored
+        let reachable = known_statuses.get(url).copied().unwrap_or(true);
+        problems.push(Problem {
+            detail: tr!(
+                reporter.lang(),
                 "{} verlinkt von {} und {} anderen",
+                "{} linked by {} and {} others",
                 url,
                 sources[0],
                 sources.len() - 1
             ),
             path: "".to_string(),
-            kind: if *known_statuses.get(url).unwrap() {
+            kind: if reachable {
                 ProblemKind::ObjectNotInList(type_name)
             } else {
                 ProblemKind::ObjectMissing(type_name)
             },
+            url: Some(url.clone()),
+            id: Some(url.clone()),
         })
     }
+    for problem in problems {
+        reporter.add_problem(problem);
+    }
     Ok(())
 }
 
+/// Pairs of fields that have to link each other, i.e. if a links to b in the first field,
+/// b has to link back to a in the second field.
+///
+/// `Organization.meeting` is the url of an external list instead of links to the meetings,
+/// so `Meeting.organization` can't be checked this way
+pub(crate) const BACKLINKS: [(&str, &str); 6] = [
+    ("Membership.person", "Person.membership"),
+    ("Membership.organization", "Organization.membership"),
+    ("Paper.superordinatedPaper", "Paper.subordinatedPaper"),
+    ("File.masterFile", "File.derivativeFile"),
+    ("Consultation.paper", "Paper.consultation"),
+    ("Consultation.agendaItem", "AgendaItem.consultation"),
+];
+
+/// Check that the links between objects exist in both directions
+///
+/// Embedded objects don't need to link back to their parent, so we only check
+/// that the object they link to embeds them
+fn analyze_backlinks(reporter: &impl Reporter) {
+    let known_ids = reporter.get_storage().known_ids.lock();
+    let field_links = reporter.get_storage().field_links.lock();
+    let empty = FieldLinks::default();
+
+    let mut problems = Vec::new();
+    for (forward, backward) in BACKLINKS {
+        let forward_links = field_links.get(forward).unwrap_or(&empty);
+        let backward_links = field_links.get(backward).unwrap_or(&empty);
+        for (links, field, backlinks, backlink_field) in [
+            (forward_links, forward, backward_links, backward),
+            (backward_links, backward, forward_links, forward),
+        ] {
+            if links.embedded {
+                continue;
+            }
+            for (source, target) in &links.links {
+                // Objects we haven't seen are reported by analyze_missing_urls
+                if !known_ids.contains(target)
+                    || backlinks.links.contains(&(target.clone(), source.clone()))
+                {
+                    continue;
+                }
+                problems.push(Problem {
+                    detail: tr!(
+                        reporter.lang(),
+                        "{} verlinkt {}, aber {} fehlt der Rückverweis in {}",
+                        "{} links {}, but {} is missing the backlink in {}",
+                        source,
+                        target,
+                        target,
+                        backlink_field
+                    ),
+                    path: field.to_string(),
+                    kind: ProblemKind::MissingBacklink(backlink_field),
+                    url: Some(target.clone()),
+                    id: Some(target.clone()),
+                });
+            }
+        }
+    }
+
+    // Release the locks before adding the problems
+    drop(known_ids);
+    drop(field_links);
+    for problem in problems {
+        reporter.add_problem(problem);
+    }
+}
+
+/// Check that live objects don't link to deleted objects
+///
+/// Links between two deleted objects are fine since deleted objects keep no other fields
+fn analyze_deleted_links(reporter: &impl Reporter) {
+    let deleted_ids = reporter.get_storage().deleted_ids.lock();
+    let field_links = reporter.get_storage().field_links.lock();
+
+    let mut problems = Vec::new();
+    for (field, links) in field_links.iter() {
+        for (source, target) in &links.links {
+            let Some(target_type) = deleted_ids.get(target) else {
+                continue;
+            };
+            if deleted_ids.contains_key(source) {
+                continue;
+            }
+            problems.push(Problem {
+                detail: tr!(
+                    reporter.lang(),
+                    "{} verlinkt das gelöschte Objekt {}",
+                    "{} links the deleted object {}",
+                    source,
+                    target
+                ),
+                path: field.to_string(),
+                kind: ProblemKind::LinkToDeletedObject(target_type),
+                url: Some(source.clone()),
+                id: Some(source.clone()),
+            });
+        }
+    }
+
+    // Release the locks before adding the problems
+    drop(deleted_ids);
+    drop(field_links);
+    for problem in problems {
+        reporter.add_problem(problem);
+    }
+}
+
 /// Validates one entire oparl API
 pub async fn validate_oparl_api<T: Reporter, C: Cache>(
     entrypoint: &str,
     reporter: &T,
     client: &OparlClient<C>,
+    options: &ValidationOptions,
 ) -> Result<()> {
     let initial_request = match client.get(entrypoint).await {
         Ok(ok) => ok,
@@ -236,18 +957,32 @@ pub async fn validate_oparl_api<T: Reporter, C: Cache>(
                 err_formatted += &format!(". Caused by: {}", cause);
             }
             reporter.add_problem(Problem {
-                detail:
-                    format!("Der Endpunkt unter {} konnte nicht erreicht werden, damit ist keine Validierung möglich: {}", entrypoint, err_formatted),
+                detail: tr!(
+                    reporter.lang(),
+                    "Der Endpunkt unter {} konnte nicht erreicht werden, damit ist keine Validierung möglich: {}",
+                    "The endpoint at {} couldn't be reached, so no validation is possible: {}",
+                    entrypoint,
+                    err_formatted
+                ),
                 path: "".to_string(),
                 kind: ProblemKind::HttpRequestFailedTooOften,
+                url: Some(entrypoint.to_string()),
+                id: None,
             });
             return Ok(());
         }
     };
 
-    let system: System = initial_request
-        .map_err(|(_, error)| Error::msg(error))
-        .context("Could not parse entrypoint into System schema")?;
+    let system: System = lenient_problems(
+        reporter,
+        initial_request
+            .map(|system| (system, Vec::new()))
+            .or_else(|(value, _)| {
+                deserialize_lenient(value, entrypoint, "System", "System", reporter.lang())
+            })
+            .map_err(|(_, error)| Error::msg(error))
+            .context("Could not parse entrypoint into System schema")?,
+    );
 
     let object = &system;
     let url = entrypoint;
@@ -256,34 +991,99 @@ pub async fn validate_oparl_api<T: Reporter, C: Cache>(
         Some(body_id) => body_id,
         None => {
             reporter.add_problem(Problem {
-                detail:
-                    "Das System-Objekt hat kein Body-Feld, damit ist keine Validierung möglich."
-                        .to_string(),
+                detail: reporter
+                    .lang()
+                    .pick(
+                        "Das System-Objekt hat kein Body-Feld, damit ist keine Validierung möglich.",
+                        "The system object has no body field, so no validation is possible.",
+                    )
+                    .to_string(),
                 path: "System.body".to_string(),
                 kind: ProblemKind::RequiredFieldMissing,
+                url: Some(entrypoint.to_string()),
+                id: system.get_id().map(ToString::to_string),
             });
             return Ok(());
         }
     };
 
     let body_list: ExternalList<Body> = match client.get(&body_id).await {
-        Ok(ok) => ok
-            .map_err(|(_, error)| Error::msg(error))
-            .context("Could not parse body list into schema")?,
+        Ok(Ok(body_list)) => body_list,
+        Ok(Err((value, _))) => lenient_problems(
+            reporter,
+            deserialize_list_lenient(value, &body_id, "Body", reporter.lang())
+                .await
+                .map_err(|(_, error)| Error::msg(error))
+                .context("Could not parse body list into schema")?,
+        ),
         Err(err) => {
             reporter.add_problem(Problem {
-                detail:
-                    format!("Der Körperschaftsliste unter {} konnte nicht erreicht werden, damit ist keine Validierung möglich: {}", *body_id, err),
+                detail: tr!(
+                    reporter.lang(),
+                    "Der Körperschaftsliste unter {} konnte nicht erreicht werden, damit ist keine Validierung möglich: {}",
+                    "The body list at {} couldn't be reached, so no validation is possible: {}",
+                    *body_id,
+                    err
+                ),
                 path: "".to_string(),
                 kind: ProblemKind::HttpRequestFailedTooOften,
+                url: Some(body_id.to_string()),
+                id: None,
             });
             return Ok(());
         }
     };
 
-    for body in body_list.data {
+    for body in &body_list.data {
         body.visit(reporter, &body_id, body.get_id(), "");
+    }
+
+    // Everything up to here is quick and runs again on resume, the checkpoint replaces its
+    // results with those of the interrupted run
+    let storage = reporter.get_storage();
+    if options.resume {
+        let checkpoint = options
+            .checkpoint
+            .as_ref()
+            .context("--resume needs a cache directory")?;
+        if checkpoint.is_file() {
+            Checkpoint::load(checkpoint)?.restore(storage);
+        } else {
+            reporter.add_message(&tr!(
+                reporter.lang(),
+                "Kein Checkpoint unter {}, die Validierung beginnt von vorne",
+                "No checkpoint at {}, starting from the beginning",
+                checkpoint.display()
+            ));
+        }
+    }
+
+    let index = if options.incremental {
+        let index_path = options
+            .index
+            .as_ref()
+            .context("--incremental needs a cache directory")?;
+        // On resume, this is the start of the interrupted run
+        storage
+            .started
+            .lock()
+            .get_or_insert_with(incremental::since_now);
+        let index = ObjectIndex::load(index_path)?;
+        if index.is_none() {
+            reporter.add_message(&tr!(
+                reporter.lang(),
+                "Kein Index unter {}, die Listen werden vollständig abgerufen",
+                "No index at {}, loading the complete lists",
+                index_path.display()
+            ));
+        }
+        index
+    } else {
+        None
+    };
 
+    for body in body_list.data {
+        let filters_step = format!("filters {}", body.get_id().unwrap_or_default());
         let first_paper_url = body.paper.context("body has no papers")?;
         let first_organization_url = body.organization.context("body has no organization")?;
         let first_person_url = body.person.context("body has no person")?;
@@ -294,21 +1094,37 @@ pub async fn validate_oparl_api<T: Reporter, C: Cache>(
         let progress_bar_person = reporter.add_bar("person");
         let progress_bar_meeting = reporter.add_bar("meeting");
 
-        let paper_future =
-            process_list::<Paper, _, _>(&first_paper_url, reporter, client, progress_bar_paper);
+        let paper_future = process_list::<Paper, _, _>(
+            &first_paper_url,
+            reporter,
+            client,
+            progress_bar_paper,
+            options,
+            index.as_ref(),
+        );
         let organization_future = process_list::<Organization, _, _>(
             &first_organization_url,
             reporter,
             client,
             progress_bar_organization,
+            options,
+            index.as_ref(),
+        );
+        let person_future = process_list::<Person, _, _>(
+            &first_person_url,
+            reporter,
+            client,
+            progress_bar_person,
+            options,
+            index.as_ref(),
         );
-        let person_future =
-            process_list::<Person, _, _>(&first_person_url, reporter, client, progress_bar_person);
         let meeting_future = process_list::<Meeting, _, _>(
             &first_meeting_url,
             reporter,
             client,
             progress_bar_meeting,
+            options,
+            index.as_ref(),
         );
 
         futures::try_join!(
@@ -317,9 +1133,69 @@ pub async fn validate_oparl_api<T: Reporter, C: Cache>(
             person_future,
             meeting_future
         )?;
+
+        if options.check_filters
+            && storage.config.any_enabled(&filters::RULES)
+            && !storage.is_completed(&filters_step)
+        {
+            let lists = [
+                (Paper::type_name(), &first_paper_url),
+                (Organization::type_name(), &first_organization_url),
+                (Person::type_name(), &first_person_url),
+                (Meeting::type_name(), &first_meeting_url),
+            ];
+            let bar = reporter.add_bar(
+                reporter
+                    .lang()
+                    .pick("Filter überprüfen", "Checking filters"),
+            );
+            bar.set_length(lists.len() as u64);
+            for (type_name, list_url) in lists {
+                bar.set_message(list_url.to_string());
+                check_filters(type_name, list_url, reporter, client).await?;
+                bar.inc(1);
+            }
+            bar.finish_with_message(reporter.lang().pick("Filter überprüft", "Checked filters"));
+            storage.complete(&filters_step);
+        }
+        if let Some(checkpoint) = &options.checkpoint {
+            Checkpoint::save(storage, checkpoint).await?;
+        }
     }
 
-    analyze_missing_urls(client, reporter).await?;
+    // Skip the checks whose rules are all disabled, loading the missing objects and the
+    // links can take a long time
+    let config = &storage.config;
+    if config.any_enabled(&["ObjectMissing", "ObjectNotInList"]) {
+        analyze_missing_urls(client, reporter).await?;
+    }
+    if config.is_enabled("MissingBacklink") {
+        analyze_backlinks(reporter);
+    }
+    if config.is_enabled("LinkToDeletedObject") {
+        analyze_deleted_links(reporter);
+    }
+    if options.check_links && config.any_enabled(&links::RULES) {
+        check_links(reporter).await?;
+    }
+    if options.revalidate {
+        report_revalidations(client, reporter);
+    }
+
+    if let (true, Some(index_path)) = (options.incremental, &options.index) {
+        ObjectIndex {
+            since: storage.started.lock().clone().expect("Set above"),
+            lists: storage.indexed_objects.lock().clone(),
+        }
+        .write(index_path)?;
+    }
+
+    // The validation is complete, so there's nothing to resume anymore
+    if let Some(checkpoint) = &options.checkpoint {
+        if checkpoint.is_file() {
+            fs_err::remove_file(checkpoint)?;
+        }
+    }
 
     Ok(())
 }
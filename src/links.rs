@@ -0,0 +1,158 @@
+//! Checks that the links to external resources such as files, web pages and licenses can be loaded
+
+#[cfg(not(target_family = "wasm"))]
+use crate::client::USER_AGENT;
+use crate::lang::tr;
+use crate::reporter::{ProgressBarWrapper, Reporter};
+use crate::semaphore::Semaphore;
+use crate::visit::{Problem, ProblemKind};
+use anyhow::Result;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use std::collections::{HashMap, HashSet};
+#[cfg(not(target_family = "wasm"))]
+use std::time::Duration;
+
+/// The kinds of problems this check reports, it's skipped if they are all disabled
+pub const RULES: [&str; 4] = [
+    ProblemKind::BrokenAccessUrl.name(),
+    ProblemKind::BrokenDownloadUrl.name(),
+    ProblemKind::DeadWebPage.name(),
+    ProblemKind::UnreachableLicense.name(),
+];
+
+/// The kind of problem for a broken url in the field `path`, or `None` if we don't check the field
+fn problem_kind(path: &str) -> Option<ProblemKind> {
+    match path.rsplit('.').next()? {
+        "accessUrl" => Some(ProblemKind::BrokenAccessUrl),
+        "downloadUrl" => Some(ProblemKind::BrokenDownloadUrl),
+        "web" | "website" => Some(ProblemKind::DeadWebPage),
+        "license" | "fileLicense" => Some(ProblemKind::UnreachableLicense),
+        _ => None,
+    }
+}
+
+/// Sends the request, returning the status if the server answered with an error
+async fn send(request: RequestBuilder) -> Result<(), (Option<StatusCode>, String)> {
+    let response = request
+        .send()
+        .await
+        .map_err(|err| (None, err.to_string()))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err((
+            Some(response.status()),
+            format!("Status {}", response.status()),
+        ))
+    }
+}
+
+/// Checks whether the url is reachable, returning the reason if it isn't
+///
+/// We only read the headers, so even the GET doesn't download the whole file
+async fn check_url(
+    client: &Client,
+    semaphore: &Semaphore,
+    url: &str,
+) -> (String, Result<(), String>) {
+    let _permit = semaphore.acquire().await.unwrap();
+    let mut result = send(client.head(url)).await;
+    // Not all servers implement HEAD, but other answers such as a 404 hold for GET as well
+    let head_unsupported = |status: Option<StatusCode>| {
+        status.is_none_or(|status| {
+            [StatusCode::METHOD_NOT_ALLOWED, StatusCode::NOT_IMPLEMENTED].contains(&status)
+        })
+    };
+    if matches!(result, Err((status, _)) if head_unsupported(status)) {
+        result = send(client.get(url)).await;
+    }
+    (url.to_string(), result.map_err(|(_, reason)| reason))
+}
+
+/// Requests all urls from the fields we check and reports those that can't be loaded
+pub async fn check_links(reporter: &impl Reporter) -> Result<()> {
+    // Copy the urls so we don't hold the lock while the requests are running
+    let links: Vec<(String, String, ProblemKind, HashSet<String>)> = reporter
+        .get_storage()
+        .known_other_urls
+        .lock()
+        .iter()
+        .filter_map(|((path, url), sources)| {
+            let kind = problem_kind(path)?;
+            let sources = sources.iter().cloned().collect();
+            Some((path.clone(), url.clone(), kind, sources))
+        })
+        .collect();
+    if links.is_empty() {
+        return Ok(());
+    }
+    let urls: HashSet<&str> = links.iter().map(|(_, url, _, _)| url.as_str()).collect();
+
+    let client = Client::builder();
+    // The wasm client doesn't support timeouts
+    #[cfg(not(target_family = "wasm"))]
+    let client = client
+        .timeout(Duration::from_secs(30))
+        .user_agent(USER_AGENT);
+    let client = client.build()?;
+    let semaphore = Semaphore::new(50);
+    let mut futures: FuturesUnordered<_> = urls
+        .iter()
+        .map(|url| check_url(&client, &semaphore, url))
+        .collect();
+
+    let lang = reporter.lang();
+    let bar = reporter.add_bar(lang.pick("Links überprüfen", "Checking links"));
+    bar.set_length(futures.len() as u64);
+    let mut statuses = HashMap::new();
+    while let Some((url, status)) = futures.next().await {
+        bar.inc(1);
+        statuses.insert(url, status);
+    }
+    drop(futures);
+
+    let mut broken = 0;
+    for (path, url, kind, sources) in links {
+        let Some(Err(reason)) = statuses.get(&url) else {
+            continue;
+        };
+        broken += 1;
+        // Sorted so the example is stable across runs
+        let mut sources: Vec<String> = sources.into_iter().collect();
+        sources.sort();
+        let linked_by = if sources.len() == 1 {
+            sources[0].clone()
+        } else {
+            tr!(
+                lang,
+                "{} Objekten, z.B. {}",
+                "{} objects, e.g. {}",
+                sources.len(),
+                sources[0]
+            )
+        };
+        reporter.add_problem(Problem {
+            detail: tr!(
+                lang,
+                "{} ({}) wird verlinkt von {}",
+                "{} ({}) is linked by {}",
+                url,
+                reason,
+                linked_by
+            ),
+            path,
+            kind,
+            url: Some(url.clone()),
+            id: Some(sources[0].clone()),
+        });
+    }
+    bar.finish_with_message(tr!(
+        lang,
+        "{} nicht erreichbare Links gefunden",
+        "Found {} unreachable links",
+        broken
+    ));
+    Ok(())
+}
@@ -1,27 +1,66 @@
+#![cfg_attr(target_family = "wasm", allow(unused_imports))]
 use anyhow::{Context as _, Result};
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use fs_err::File;
 #[cfg(not(target_family = "wasm"))]
+use oparl_validator_rs::archive::ArchiveCache;
+use oparl_validator_rs::baseline::Baseline;
+#[cfg(not(target_family = "wasm"))]
 use oparl_validator_rs::batch::validate_all;
-use oparl_validator_rs::cli::{problem_report, write_detail_report, ReporterCli};
-use oparl_validator_rs::client::{FileCache, NoCache, OparlClient};
+use oparl_validator_rs::cache_dir::CacheAction;
+use oparl_validator_rs::checkpoint::CHECKPOINT_FILE;
+use oparl_validator_rs::cli::{
+    problem_report, write_detail_report, write_machine_report, ReportFormat, ReporterCli,
+};
+use oparl_validator_rs::client::{find_system, FileCache, NoCache, OparlClient};
+use oparl_validator_rs::config::Config;
+use oparl_validator_rs::diff::{RunDiff, RunSummary};
 use oparl_validator_rs::endpoints::get_endpoints;
+use oparl_validator_rs::explain::write_explanation;
+use oparl_validator_rs::incremental::INDEX_FILE;
+use oparl_validator_rs::lang::{tr, Lang};
 use oparl_validator_rs::reporter::Reporter;
 use oparl_validator_rs::storage::Storage;
-use oparl_validator_rs::validate_oparl_api;
+use oparl_validator_rs::visit::Severity;
+use oparl_validator_rs::{validate_oparl_api, ValidationOptions};
 use std::io;
 use std::io::BufWriter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+/// Checks OParl endpoints for violations of the spec and other common problems
 #[derive(Debug, Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Options {
-    name_or_url: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The url of the system, the name from endpoints.yml or `all`
+    #[arg(required = true)]
+    name_or_url: Option<String>,
     #[arg(long)]
     cache: Option<PathBuf>,
+    /// The language of messages and reports, overrides the config file [default: de]
+    #[arg(long, global = true)]
+    lang: Option<Lang>,
+    #[command(flatten)]
+    run: RunOptions,
+}
+
+/// The options for a validation and its reports, both for endpoints and for archives
+#[derive(Debug, Args)]
+struct RunOptions {
     /// Write a report with both summary and every single case to this file
     #[arg(long, default_value = "report.txt")]
     report: PathBuf,
+    /// Also write a machine-readable report next to the text report, e.g. report.json
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    format: ReportFormat,
     /// Write progress to this file
     #[cfg_attr(target_family = "wasm", allow(dead_code))]
     #[arg(long, default_value = "log.txt")]
@@ -29,74 +68,320 @@ struct Options {
     /// Suppress output to the console
     #[arg(long)]
     quiet: bool,
+    /// Yaml file with the severity of each problem kind, see the readme
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Override the severity of a problem kind, e.g. `--severity UsingHttp=error`
+    #[arg(long, value_name = "KIND=SEVERITY")]
+    severity: Vec<String>,
+    /// Exit with code 2 if there are problems with at least this severity
+    #[arg(long)]
+    fail_on: Option<Severity>,
+    /// Only check these rules, by code (e.g. OPARL007) or name (e.g. UsingHttp)
+    #[arg(long, value_delimiter = ',', value_name = "RULE")]
+    select: Vec<String>,
+    /// Don't check these rules, by code (e.g. OPARL007) or name (e.g. UsingHttp)
+    #[arg(long, value_delimiter = ',', value_name = "RULE")]
+    ignore: Vec<String>,
+    /// Report the problems from this baseline file separately and ignore them for `--fail-on`
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Write all problems of this run to a baseline file for `--baseline`
+    #[arg(long)]
+    write_baseline: Option<PathBuf>,
+    #[command(flatten)]
+    validation: ValidationOptions,
+}
+
+/// Reads the config file and applies the overrides from the command line
+fn load_config(
+    path: Option<&Path>,
+    severity: &[String],
+    select: &[String],
+    ignore: &[String],
+    lang: Option<Lang>,
+) -> Result<Config> {
+    let mut config = match path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    for severity in severity {
+        config.add_severity_override(severity)?;
+    }
+    for rule in select {
+        config.add_select(rule)?;
+    }
+    for rule in ignore {
+        config.add_ignore(rule)?;
+    }
+    if let Some(lang) = lang {
+        config.lang = lang;
+    }
+    Ok(config)
+}
+
+impl RunOptions {
+    fn config(&self, lang: Option<Lang>) -> Result<Config> {
+        load_config(
+            self.config.as_deref(),
+            &self.severity,
+            &self.select,
+            &self.ignore,
+            lang,
+        )
+    }
+
+    fn storage(&self, config: Config) -> Result<Storage> {
+        let mut storage = Storage::with_config(config);
+        if let Some(baseline) = &self.baseline {
+            storage = storage.with_baseline(Baseline::load(baseline)?);
+        }
+        Ok(storage)
+    }
+
+    /// Writes the reports and the baseline, returning whether there are problems at or above
+    /// the `--fail-on` severity
+    fn write_reports(&self, reporter: &ReporterCli) -> Result<bool> {
+        let mut report = BufWriter::new(File::create(&self.report)?);
+        problem_report(
+            reporter.get_storage(),
+            &mut io::stdout().lock(),
+            &mut report,
+            true,
+        )?;
+        write_detail_report(
+            reporter.get_storage(),
+            &mut io::stdout().lock(),
+            &mut report,
+        )?;
+        if let Some(extension) = self.format.extension() {
+            let mut machine_report =
+                BufWriter::new(File::create(self.report.with_extension(extension))?);
+            write_machine_report(reporter.get_storage(), self.format, &mut machine_report)?;
+        }
+        if let Some(write_baseline) = &self.write_baseline {
+            let mut baseline_file = BufWriter::new(File::create(write_baseline)?);
+            Baseline::from_storage(reporter.get_storage()).write(&mut baseline_file)?;
+        }
+
+        Ok(fails(self.fail_on, reporter.get_storage().max_severity()))
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Show which problems were fixed, are new or changed between two runs of an endpoint
+    ///
+    /// Each run is either a report from `--format json` or `--format ndjson`, or a cache
+    /// directory that is validated again
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Yaml file with the rules to compare, see the readme
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Only compare these rules, by code (e.g. OPARL007) or name (e.g. UsingHttp)
+        #[arg(long, value_delimiter = ',', value_name = "RULE")]
+        select: Vec<String>,
+        /// Don't compare these rules, by code (e.g. OPARL007) or name (e.g. UsingHttp)
+        #[arg(long, value_delimiter = ',', value_name = "RULE")]
+        ignore: Vec<String>,
+    },
+    /// Explain a rule with examples and how to fix it
+    Explain {
+        /// The code (e.g. OPARL007) or the name (e.g. UsingHttp) of the rule
+        rule: String,
+    },
+    /// Inspect and clean up the cache directories from `--cache`
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Pack a cache directory from `--cache` into a single zstd-compressed archive
+    #[cfg(not(target_family = "wasm"))]
+    Pack {
+        cache_dir: PathBuf,
+        /// The archive to write, e.g. cache.tar.zst
+        archive: PathBuf,
+    },
+    /// Validate the endpoint whose responses are in an archive from `pack`
+    ///
+    /// Responses that are missing from the archive are downloaded and added to the archive
+    #[cfg(not(target_family = "wasm"))]
+    ValidateArchive {
+        archive: PathBuf,
+        #[command(flatten)]
+        run: Box<RunOptions>,
+    },
 }
 
+/// Runs a subcommand, returning whether there are problems at or above the `--fail-on` severity
 #[cfg_attr(target_family = "wasm", allow(dead_code))]
-async fn main_cli() -> Result<()> {
-    let options: Options = Options::parse();
+async fn run_command(command: &Command, lang: Option<Lang>) -> Result<bool> {
+    match command {
+        Command::Diff {
+            old,
+            new,
+            config,
+            select,
+            ignore,
+        } => {
+            let config = load_config(config.as_deref(), &[], select, ignore, lang)?;
+            let old = RunSummary::load(old, &config).await?;
+            let new = RunSummary::load(new, &config).await?;
+            RunDiff::new(&old, &new).write(&mut io::stdout().lock(), config.lang)?;
+        }
+        Command::Explain { rule } => {
+            write_explanation(rule, lang.unwrap_or_default(), &mut io::stdout().lock())?
+        }
+        Command::Cache { action } => {
+            return action.run(&mut io::stdout().lock(), lang.unwrap_or_default())
+        }
+        #[cfg(not(target_family = "wasm"))]
+        Command::Pack { cache_dir, archive } => {
+            let count = ArchiveCache::pack(cache_dir, archive)?;
+            let packed = tr!(
+                lang.unwrap_or_default(),
+                "{} Dateien in {} gepackt",
+                "{} files packed into {}",
+                count,
+                archive.display()
+            );
+            println!("{}", packed);
+        }
+        #[cfg(not(target_family = "wasm"))]
+        Command::ValidateArchive { archive, run } => {
+            return validate_archive(archive, run, lang).await
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(not(target_family = "wasm"))]
+async fn validate_archive(archive: &Path, run: &RunOptions, lang: Option<Lang>) -> Result<bool> {
+    if run.validation.resume || run.validation.incremental || run.validation.cache_max_age.is_some()
+    {
+        anyhow::bail!(
+            "--resume, --incremental and --cache-max-age need --cache, they don't work with archives"
+        );
+    }
+    let cache = ArchiveCache::open(archive)?;
+    let urls = cache.entries().into_iter().map(|entry| entry.url).collect();
+    let endpoint_url = find_system(&cache, urls)?
+        .with_context(|| format!("There is no system in {}", archive.display()))?;
+    let cache = cache.with_legacy_prefix(endpoint_url.trim_end_matches("system").to_string());
+
+    let reporter = ReporterCli::new(run.storage(run.config(lang)?)?, run.quiet, None);
+    let client = OparlClient::new(cache).with_revalidate(run.validation.revalidate);
+    let result = validate_oparl_api(&endpoint_url, &reporter, &client, &run.validation).await;
+    // Also after a failed run, so that the next attempt doesn't download everything again
+    client.cache.write()?;
+    result?;
+    run.write_reports(&reporter)
+}
+
+/// Returns whether there are problems at or above the `--fail-on` severity
+#[cfg_attr(target_family = "wasm", allow(dead_code))]
+async fn main_cli() -> Result<bool> {
+    let mut options: Options = Options::parse();
+    if let Some(command) = &options.command {
+        return run_command(command, options.lang).await;
+    }
+    let name_or_url = options.name_or_url.clone().context("Missing endpoint")?;
+    let run = &mut options.run;
+
+    let config = run.config(options.lang)?;
+    if run.validation.resume && options.cache.is_none() {
+        anyhow::bail!("--resume needs --cache, the checkpoint is stored in the cache directory");
+    }
+    if run.validation.incremental && options.cache.is_none() {
+        anyhow::bail!("--incremental needs --cache, the index is stored in the cache directory");
+    }
+    if run.validation.cache_max_age.is_some() && options.cache.is_none() {
+        anyhow::bail!("--cache-max-age needs --cache");
+    }
+    if run.validation.revalidate && options.cache.is_none() {
+        anyhow::bail!("--revalidate needs --cache, there's nothing to revalidate without a cache");
+    }
 
     #[cfg(not(target_family = "wasm"))]
-    if options.name_or_url == "all" {
-        validate_all(&options.cache, &options.report, &options.log, options.quiet).await?;
-        return Ok(());
+    if name_or_url == "all" {
+        if run.baseline.is_some() || run.write_baseline.is_some() {
+            anyhow::bail!("Baselines are only supported for a single endpoint");
+        }
+        let max_severity = validate_all(
+            &options.cache,
+            &run.report,
+            &run.log,
+            run.quiet,
+            run.format,
+            &run.validation,
+            &config,
+        )
+        .await?;
+        return Ok(fails(run.fail_on, max_severity));
     }
 
-    let endpoint_url = if options.name_or_url.starts_with("http") {
-        options.name_or_url
+    let endpoint_url = if name_or_url.starts_with("http") {
+        name_or_url
     } else {
         let endpoints = get_endpoints(&NoCache).await?;
 
         let endpoint = endpoints
             .iter()
-            .find(|x| x.title == options.name_or_url)
-            .context(format!(
-                "No endpoint with name '{}' found",
-                options.name_or_url
-            ))?;
+            .find(|x| x.title == name_or_url)
+            .context(format!("No endpoint with name '{}' found", name_or_url))?;
 
         endpoint.url.clone()
     };
 
-    let reporter = ReporterCli::new(Storage::new(), options.quiet, None); // TODO: Log file
+    let reporter = ReporterCli::new(run.storage(config)?, run.quiet, None); // TODO: Log file
     if let Some(cache_dir) = options.cache.clone() {
+        run.validation.checkpoint = Some(cache_dir.join(CHECKPOINT_FILE));
+        run.validation.index = Some(cache_dir.join(INDEX_FILE));
         validate_oparl_api::<_, _>(
             &endpoint_url,
             &reporter,
-            &OparlClient::new(FileCache::new(
-                cache_dir,
-                endpoint_url.trim_end_matches("system").to_string(),
-            )),
+            &OparlClient::new(
+                FileCache::new(cache_dir)?
+                    .with_legacy_prefix(endpoint_url.trim_end_matches("system").to_string())
+                    .with_max_age(run.validation.cache_max_age),
+            )
+            .with_revalidate(run.validation.revalidate),
+            &run.validation,
         )
         .await?;
     } else {
-        validate_oparl_api::<_, _>(&endpoint_url, &reporter, &OparlClient::new(NoCache)).await?;
-    }
-
-    let mut report = BufWriter::new(File::create(options.report)?);
-    problem_report(
-        reporter.get_storage(),
-        &mut io::stdout().lock(),
-        &mut report,
-        true,
-    )?;
-    write_detail_report(
-        reporter.get_storage(),
-        &mut io::stdout().lock(),
-        &mut report,
-    )?;
+        validate_oparl_api::<_, _>(
+            &endpoint_url,
+            &reporter,
+            &OparlClient::new(NoCache),
+            &run.validation,
+        )
+        .await?;
+    }
 
-    Ok(())
+    run.write_reports(&reporter)
+}
+
+#[cfg_attr(target_family = "wasm", allow(dead_code))]
+fn fails(fail_on: Option<Severity>, max_severity: Option<Severity>) -> bool {
+    fail_on.is_some_and(|fail_on| max_severity >= Some(fail_on))
 }
 
 #[cfg(not(target_family = "wasm"))]
 #[tokio::main]
 async fn main() {
-    if let Err(e) = main_cli().await {
-        eprintln!("💥 The validator failed: This is a bug");
-        for cause in e.chain().collect::<Vec<_>>().iter() {
-            eprintln!("  Caused by: {}", cause);
+    match main_cli().await {
+        Ok(false) => {}
+        Ok(true) => std::process::exit(2),
+        Err(e) => {
+            eprintln!("💥 The validator failed: This is a bug");
+            for cause in e.chain().collect::<Vec<_>>().iter() {
+                eprintln!("  Caused by: {}", cause);
+            }
+            std::process::exit(1);
         }
-        std::process::exit(1);
     }
 }
 
@@ -0,0 +1,232 @@
+//! Checks that `pagination` and `links` of an external list are consistent across its pages
+
+use crate::external_list::ExternalList;
+use crate::lang::{tr, Lang};
+use crate::visit::{Problem, ProblemKind};
+use serde::{Deserialize, Serialize};
+
+/// Tracks the metadata of one external list while we go through its pages
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PaginationChecker {
+    /// The url of the list from the body, which may differ from `links.first`
+    list_url: String,
+    page_count: usize,
+    object_count: usize,
+    current_page: Option<usize>,
+    total_elements: Option<usize>,
+    total_pages: Option<usize>,
+    first: Option<String>,
+    last: Option<String>,
+    /// If a page failed, the counts can't be compared anymore
+    complete: bool,
+    /// The language of the problem details
+    lang: Lang,
+}
+
+impl PaginationChecker {
+    pub fn new(list_url: &str, lang: Lang) -> Self {
+        Self {
+            list_url: list_url.to_string(),
+            page_count: 0,
+            object_count: 0,
+            current_page: None,
+            total_elements: None,
+            total_pages: None,
+            first: None,
+            last: None,
+            complete: true,
+            lang,
+        }
+    }
+
+    /// A page couldn't be loaded or used, so the totals can't be checked anymore
+    pub fn page_failed(&mut self) {
+        self.complete = false;
+        self.page_count += 1;
+        self.current_page = None;
+    }
+
+    /// Checks the metadata of a single page against the previous pages
+    pub fn check_page<T>(&mut self, page: &ExternalList<T>, url: &str) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut add_problem = |kind: ProblemKind, path: &str, detail: String| {
+            problems.push(Problem {
+                detail: format!("{}: {}", url, detail),
+                path: format!("ExternalList.{}", path),
+                kind,
+                url: Some(url.to_string()),
+                id: None,
+            })
+        };
+        let lang = self.lang;
+        let is_first_page = self.page_count == 0;
+        let is_last_page = page.links.next.is_none();
+        self.page_count += 1;
+        self.object_count += page.data.len();
+
+        let pagination = &page.pagination;
+        if let Some(current_page) = pagination.current_page {
+            let expected = self.current_page.map(|previous| previous + 1).or_else(|| {
+                // We can't know the number after a failed page
+                is_first_page.then_some(1)
+            });
+            if let Some(expected) = expected.filter(|expected| *expected != current_page) {
+                add_problem(
+                    ProblemKind::PaginationCurrentPage,
+                    "pagination.currentPage",
+                    tr!(
+                        lang,
+                        "{} statt {}",
+                        "{} instead of {}",
+                        current_page,
+                        expected
+                    ),
+                );
+            }
+        }
+        self.current_page = pagination.current_page;
+
+        if let Some(elements_per_page) = pagination.elements_per_page {
+            if (!is_last_page && page.data.len() != elements_per_page)
+                || page.data.len() > elements_per_page
+            {
+                add_problem(
+                    ProblemKind::PaginationElementsPerPage,
+                    "pagination.elementsPerPage",
+                    tr!(
+                        lang,
+                        "{} angegeben, aber {} Objekte auf der Seite",
+                        "{} given, but {} objects on the page",
+                        elements_per_page,
+                        page.data.len()
+                    ),
+                );
+            }
+        }
+        if pagination.total_elements.is_some() {
+            self.total_elements = pagination.total_elements;
+        }
+        if pagination.total_pages.is_some() {
+            self.total_pages = pagination.total_pages;
+        }
+
+        let links = &page.links;
+        for (name, link) in [
+            ("first", &links.first),
+            ("self", &links.self_),
+            ("last", &links.last),
+        ] {
+            if link.is_none() {
+                add_problem(
+                    ProblemKind::ListLinkMissing(name),
+                    &format!("links.{}", name),
+                    lang.pick("Der Link fehlt", "The link is missing")
+                        .to_string(),
+                );
+            }
+        }
+
+        if let Some(self_) = &links.self_ {
+            // The url from the body usually is the first page without a page parameter
+            let is_first_url =
+                is_first_page && (links.first.as_ref() == Some(self_) || self_ == &self.list_url);
+            if self_ != url && !is_first_url {
+                add_problem(
+                    ProblemKind::ListLinkWrong("self"),
+                    "links.self",
+                    tr!(
+                        lang,
+                        "Der Link zeigt auf {}",
+                        "The link points to {}",
+                        self_
+                    ),
+                );
+            }
+        }
+        for (name, link, previous) in [
+            ("first", &links.first, &mut self.first),
+            ("last", &links.last, &mut self.last),
+        ] {
+            if let (Some(link), Some(previous)) = (link, &previous) {
+                if link != previous {
+                    add_problem(
+                        ProblemKind::ListLinkWrong(name),
+                        &format!("links.{}", name),
+                        tr!(
+                            lang,
+                            "Der Link zeigt auf {} statt wie zuvor auf {}",
+                            "The link points to {} instead of {} as before",
+                            link,
+                            previous
+                        ),
+                    );
+                }
+            }
+            if link.is_some() {
+                *previous = link.clone();
+            }
+        }
+        if let (true, Some(last)) = (is_last_page, &links.last) {
+            let is_last_url = links.self_.as_ref() == Some(last) || last == url;
+            if !is_last_url {
+                add_problem(
+                    ProblemKind::ListLinkWrong("last"),
+                    "links.last",
+                    tr!(
+                        lang,
+                        "Die Seite hat keinen next-Link, aber der last-Link zeigt auf {}",
+                        "The page has no next link, but the last link points to {}",
+                        last
+                    ),
+                );
+            }
+        }
+
+        problems
+    }
+
+    /// Checks the totals after the last page
+    pub fn finish(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if !self.complete {
+            return problems;
+        }
+        if let Some(total_elements) = self.total_elements {
+            if total_elements != self.object_count {
+                problems.push(Problem {
+                    detail: tr!(
+                        self.lang,
+                        "{}: {} angegeben, aber {} Objekte in der Liste",
+                        "{}: {} given, but {} objects in the list",
+                        self.list_url,
+                        total_elements,
+                        self.object_count
+                    ),
+                    path: "ExternalList.pagination.totalElements".to_string(),
+                    kind: ProblemKind::PaginationTotalElements,
+                    url: Some(self.list_url.clone()),
+                    id: None,
+                });
+            }
+        }
+        if let Some(total_pages) = self.total_pages {
+            if total_pages != self.page_count {
+                problems.push(Problem {
+                    detail: tr!(
+                        self.lang,
+                        "{}: {} angegeben, aber {} Seiten in der Liste",
+                        "{}: {} given, but {} pages in the list",
+                        self.list_url,
+                        total_pages,
+                        self.page_count
+                    ),
+                    path: "ExternalList.pagination.totalPages".to_string(),
+                    kind: ProblemKind::PaginationTotalPages,
+                    url: Some(self.list_url.clone()),
+                    id: None,
+                });
+            }
+        }
+        problems
+    }
+}
@@ -1,3 +1,4 @@
+use crate::lang::Lang;
 use crate::storage::Storage;
 use crate::visit::Problem;
 use std::borrow::Cow;
@@ -10,6 +11,11 @@ pub trait Reporter {
 
     fn get_storage(&self) -> &Storage;
 
+    /// The language for problem details and progress messages
+    fn lang(&self) -> Lang {
+        self.get_storage().config.lang
+    }
+
     /// Adds the id of an oparl objects (those we have seen)
     fn add_id(&self, id: String) {
         self.get_storage().add_id(id);
@@ -20,9 +26,15 @@ pub trait Reporter {
         self.get_storage().add_link(type_name, target, source);
     }
 
-    /// Adds a URL pointing to an external resource
-    fn add_other_url(&self, url: String) {
-        self.get_storage().add_other_url(url);
+    /// Adds a link between two objects in the field `path`, e.g. `Paper.subordinatedPaper`
+    fn add_field_link(&self, path: &str, source: String, target: String, embedded: bool) {
+        self.get_storage()
+            .add_field_link(path, source, target, embedded);
+    }
+
+    /// Adds a URL pointing to an external resource from the field `path`, e.g. `File.accessUrl`
+    fn add_other_url(&self, path: &str, url: String, source: String) {
+        self.get_storage().add_other_url(path, url, source);
     }
 
     fn add_bar(&self, name: impl Into<Cow<'static, str>>) -> Self::ProgressBar;
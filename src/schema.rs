@@ -31,6 +31,30 @@ impl Deref for OtherUrl {
     }
 }
 
+/// A day in the `date` format, e.g. `2023-01-31`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Date(String);
+
+impl Deref for Date {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A point in time in the `date-time` format, e.g. `2023-01-31T18:00:00+01:00`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DateTime(String);
+
+impl Deref for DateTime {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct File {
@@ -39,7 +63,7 @@ pub struct File {
     pub name: Option<String>,
     pub file_name: Option<String>,
     pub mime_type: Option<String>,
-    pub date: Option<String>,
+    pub date: Option<Date>,
     pub size: Option<usize>,
     pub sha1_checksum: Option<String>,
     pub text: Option<String>,
@@ -53,8 +77,8 @@ pub struct File {
     pub agenda_item: Option<Vec<OparlUrl<AgendaItem>>>,
     pub paper: Option<Vec<OparlUrl<Paper>>>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -104,6 +128,54 @@ impl OparlObject for File {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "name",
+            "fileName",
+            "mimeType",
+            "date",
+            "size",
+            "sha1Checksum",
+            "text",
+            "accessUrl",
+            "downloadUrl",
+            "externalServiceUrl",
+            "masterFile",
+            "derivativeFile",
+            "fileLicense",
+            "meeting",
+            "agendaItem",
+            "paper",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -121,8 +193,8 @@ pub struct System {
     pub website: Option<OtherUrl>,
     pub vendor: Option<OtherUrl>,
     pub product: Option<OtherUrl>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -165,6 +237,47 @@ impl OparlObject for System {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "oparlVersion",
+            "otherOparlVersions",
+            "license",
+            "body",
+            "name",
+            "contactEmail",
+            "contactName",
+            "website",
+            "vendor",
+            "product",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -181,14 +294,14 @@ pub struct Organization {
     pub sub_organization_of: Option<OparlUrl<Organization>>,
     pub organization_type: Option<String>,
     pub classification: Option<String>,
-    pub start_date: Option<String>,
-    pub end_date: Option<String>,
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
     pub website: Option<OtherUrl>,
     pub location: Option<Location>,
     pub external_body: Option<OparlUrl<Body>>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -236,6 +349,52 @@ impl OparlObject for Organization {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "body",
+            "name",
+            "membership",
+            "meeting",
+            "shortName",
+            "post",
+            "subOrganizationOf",
+            "organizationType",
+            "classification",
+            "startDate",
+            "endDate",
+            "website",
+            "location",
+            "externalBody",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -280,6 +439,29 @@ impl OparlObject for Consultation {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "paper",
+            "agendaItem",
+            "meeting",
+            "organization",
+            "authoritative",
+            "role",
+            "keyword",
+            "web",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -290,7 +472,7 @@ pub struct Paper {
     pub body: Option<OparlUrl<Body>>,
     pub name: Option<String>,
     pub reference: Option<String>,
-    pub date: Option<String>,
+    pub date: Option<Date>,
     pub paper_type: Option<String>,
     pub related_paper: Option<Vec<OparlUrl<Paper>>>,
     pub superordinated_paper: Option<Vec<OparlUrl<Paper>>>,
@@ -303,8 +485,8 @@ pub struct Paper {
     pub originator_organization: Option<Vec<OparlUrl<Organization>>>,
     pub consultation: Option<Vec<Consultation>>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -358,6 +540,53 @@ impl OparlObject for Paper {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "body",
+            "name",
+            "reference",
+            "date",
+            "paperType",
+            "relatedPaper",
+            "superordinatedPaper",
+            "subordinatedPaper",
+            "mainFile",
+            "auxiliaryFile",
+            "location",
+            "originatorPerson",
+            "underDirectionOf",
+            "originatorOrganization",
+            "consultation",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -367,8 +596,8 @@ pub struct LegislativeTerm {
     pub r#type: Option<String>,
     pub body: Option<OparlUrl<Body>>,
     pub name: Option<String>,
-    pub start_date: Option<String>,
-    pub end_date: Option<String>,
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
     pub keyword: Option<Vec<String>>,
     pub web: Option<OtherUrl>,
     #[serde(flatten)]
@@ -398,6 +627,27 @@ impl OparlObject for LegislativeTerm {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "body",
+            "name",
+            "startDate",
+            "endDate",
+            "keyword",
+            "web",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -410,8 +660,8 @@ pub struct Body {
     pub name: Option<String>,
     pub website: Option<OtherUrl>,
     pub license: Option<OtherUrl>,
-    pub license_valid_since: Option<String>,
-    pub oparl_since: Option<String>,
+    pub license_valid_since: Option<DateTime>,
+    pub oparl_since: Option<DateTime>,
     pub ags: Option<String>,
     pub rgs: Option<String>,
     pub equivalent: Option<Vec<OtherUrl>>,
@@ -425,8 +675,8 @@ pub struct Body {
     pub classification: Option<String>,
     pub location: Option<Location>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -488,6 +738,57 @@ impl OparlObject for Body {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "system",
+            "shortName",
+            "name",
+            "website",
+            "license",
+            "licenseValidSince",
+            "oparlSince",
+            "ags",
+            "rgs",
+            "equivalent",
+            "contactEmail",
+            "contactName",
+            "organization",
+            "person",
+            "meeting",
+            "paper",
+            "legislativeTerm",
+            "classification",
+            "location",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -504,8 +805,8 @@ pub struct AgendaItem {
     pub resolution_text: Option<String>,
     pub resolution_file: Option<File>,
     pub auxiliary_file: Option<Vec<File>>,
-    pub start: Option<String>,
-    pub end: Option<String>,
+    pub start: Option<DateTime>,
+    pub end: Option<DateTime>,
     pub keyword: Option<Vec<String>>,
     pub web: Option<OtherUrl>,
     #[serde(flatten)]
@@ -542,6 +843,34 @@ impl OparlObject for AgendaItem {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "meeting",
+            "number",
+            "name",
+            "public",
+            "consultation",
+            "result",
+            "resolutionText",
+            "resolutionFile",
+            "auxiliaryFile",
+            "start",
+            "end",
+            "keyword",
+            "web",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -552,8 +881,8 @@ pub struct Meeting {
     pub name: Option<String>,
     pub meeting_state: Option<String>,
     pub cancelled: Option<bool>,
-    pub start: Option<String>,
-    pub end: Option<String>,
+    pub start: Option<DateTime>,
+    pub end: Option<DateTime>,
     pub location: Option<Location>,
     pub organization: Option<Vec<OparlUrl<Organization>>>,
     pub participant: Option<Vec<OparlUrl<Person>>>,
@@ -563,8 +892,8 @@ pub struct Meeting {
     pub auxiliary_file: Option<Vec<File>>,
     pub agenda_item: Option<Vec<AgendaItem>>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -606,6 +935,51 @@ impl OparlObject for Meeting {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "name",
+            "meetingState",
+            "cancelled",
+            "start",
+            "end",
+            "location",
+            "organization",
+            "participant",
+            "invitation",
+            "resultsProtocol",
+            "verbatimProtocol",
+            "auxiliaryFile",
+            "agendaItem",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -625,8 +999,8 @@ pub struct Location {
     pub meeting: Option<Vec<OparlUrl<Meeting>>>,
     pub papers: Option<Vec<OparlUrl<Paper>>>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -666,6 +1040,49 @@ impl OparlObject for Location {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "description",
+            "geojson",
+            "streetAddress",
+            "room",
+            "postalCode",
+            "subLocality",
+            "locality",
+            "bodies",
+            "organization",
+            "meeting",
+            "papers",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -677,8 +1094,8 @@ pub struct Membership {
     pub organization: Option<OparlUrl<Organization>>,
     pub role: Option<String>,
     pub voting_right: Option<bool>,
-    pub start_date: Option<String>,
-    pub end_date: Option<String>,
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
     pub on_behalf_of: Option<OparlUrl<Organization>>,
     pub keyword: Option<Vec<String>>,
     pub web: Option<OtherUrl>,
@@ -712,6 +1129,30 @@ impl OparlObject for Membership {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "person",
+            "organization",
+            "role",
+            "votingRight",
+            "startDate",
+            "endDate",
+            "onBehalfOf",
+            "keyword",
+            "web",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -735,8 +1176,8 @@ pub struct Person {
     pub life: Option<String>,
     pub life_source: Option<String>,
     pub keyword: Option<Vec<String>>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
     pub web: Option<OtherUrl>,
     pub deleted: Option<bool>,
     #[serde(flatten)]
@@ -780,4 +1221,51 @@ impl OparlObject for Person {
     fn get_id(&self) -> Option<&str> {
         self.id.as_ref().map(|x| x.as_str())
     }
+
+    fn get_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "id",
+            "type",
+            "body",
+            "name",
+            "familyName",
+            "givenName",
+            "formOfAddress",
+            "affix",
+            "title",
+            "gender",
+            "phone",
+            "email",
+            "location",
+            "status",
+            "membership",
+            "life",
+            "lifeSource",
+            "keyword",
+            "created",
+            "modified",
+            "web",
+            "deleted",
+        ]
+    }
+
+    fn get_other(&self) -> &HashMap<String, Value> {
+        &self.other
+    }
+
+    fn get_created(&self) -> Option<&str> {
+        self.created.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        self.modified.as_deref().map(|x| x.as_str())
+    }
+
+    fn get_deleted(&self) -> bool {
+        self.deleted.unwrap_or(false)
+    }
 }
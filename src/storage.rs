@@ -1,16 +1,61 @@
-use crate::visit::Problem;
+use crate::baseline::Baseline;
+use crate::checkpoint::ListProgress;
+use crate::config::Config;
+use crate::incremental::IndexedObject;
+use crate::visit::{Problem, Severity};
 use crate::ProblemKind;
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
 
 type TypeAndUrl = (&'static str, String);
 
+/// The links from one field, e.g. all `Paper.subordinatedPaper` links
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct FieldLinks {
+    /// Whether the field contains embedded objects instead of urls
+    pub embedded: bool,
+    /// Pairs of (source id, target id)
+    pub links: HashSet<(String, String)>,
+}
+
+/// The fields of an object in an external list that the list filters work on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListObject {
+    pub id: String,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub deleted: bool,
+}
+
 #[derive(Default)]
 pub struct Storage {
     pub problems: Mutex<Vec<Problem>>,
     pub known_ids: Mutex<HashSet<String>>,
-    pub known_other_urls: Mutex<HashSet<String>>,
+    /// Urls of external resources by field and url, with the objects linking them
+    pub known_other_urls: Mutex<HashMap<(String, String), Vec<String>>>,
     pub linked_ids: Mutex<HashMap<TypeAndUrl, Vec<String>>>,
+    /// Links by the field they are in, for checking that both sides link each other
+    pub field_links: Mutex<HashMap<String, FieldLinks>>,
+    /// The objects of each external list by the url of the list
+    pub list_objects: Mutex<HashMap<String, Vec<ListObject>>>,
+    /// The ids of the objects with `deleted: true` with their type
+    pub deleted_ids: Mutex<HashMap<String, &'static str>>,
+    /// The position in the external lists we're going through, by the url of the list
+    pub list_progress: Mutex<HashMap<String, ListProgress>>,
+    /// The lists and other steps that are finished, so that `--resume` skips them
+    pub completed_steps: Mutex<HashSet<String>>,
+    /// The objects of each finished external list for the index of `--incremental`
+    pub indexed_objects: Mutex<HashMap<String, Vec<IndexedObject>>>,
+    /// When the validation started, kept across `--resume` so that the index doesn't miss changes
+    pub started: Mutex<Option<String>>,
+    /// When we last wrote a checkpoint, which isn't part of the checkpoint itself
+    pub last_checkpoint: Mutex<Option<Instant>>,
+    /// How the problems are reported
+    pub config: Config,
+    /// The problems known from an earlier run, which are reported separately
+    pub baseline: Option<Baseline>,
 }
 
 impl Storage {
@@ -20,10 +65,45 @@ impl Storage {
             known_ids: Default::default(),
             known_other_urls: Default::default(),
             linked_ids: Default::default(),
+            field_links: Default::default(),
+            list_objects: Default::default(),
+            deleted_ids: Default::default(),
+            list_progress: Default::default(),
+            completed_steps: Default::default(),
+            indexed_objects: Default::default(),
+            started: Default::default(),
+            last_checkpoint: Default::default(),
+            config: Default::default(),
+            baseline: None,
+        }
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Storage {
+            config,
+            ..Storage::new()
+        }
+    }
+
+    pub fn with_baseline(self, baseline: Baseline) -> Self {
+        Storage {
+            baseline: Some(baseline),
+            ..self
         }
     }
 
+    /// Whether the problem was already in the baseline
+    pub fn is_known(&self, problem: &Problem) -> bool {
+        self.baseline
+            .as_ref()
+            .is_some_and(|baseline| baseline.contains(problem))
+    }
+
+    /// Records the problem unless its rule is disabled with `--select` or `--ignore`
     pub fn add_problem(&self, problem: Problem) {
+        if !self.config.is_enabled(problem.kind.name()) {
+            return;
+        }
         self.problems.lock().push(problem);
     }
 
@@ -41,22 +121,113 @@ impl Storage {
             .push(source)
     }
 
+    /// Adds a link between two objects in the field `path`, e.g. `Paper.subordinatedPaper`
+    pub fn add_field_link(&self, path: &str, source: String, target: String, embedded: bool) {
+        let mut field_links = self.field_links.lock();
+        let field_links = field_links.entry(path.to_string()).or_default();
+        field_links.embedded |= embedded;
+        field_links.links.insert((source, target));
+    }
+
+    /// Adds an object we've seen in the external list `list_url`
+    pub fn add_list_object(&self, list_url: &str, object: ListObject) {
+        self.list_objects
+            .lock()
+            .entry(list_url.to_string())
+            .or_default()
+            .push(object)
+    }
+
+    /// Adds the id of an object with `deleted: true`
+    pub fn add_deleted(&self, type_name: &'static str, id: String) {
+        self.deleted_ids.lock().insert(id, type_name);
+    }
+
+    /// Whether a list or another step was finished before the checkpoint we resumed from
+    pub fn is_completed(&self, step: &str) -> bool {
+        self.completed_steps.lock().contains(step)
+    }
+
+    pub fn complete(&self, step: &str) {
+        self.completed_steps.lock().insert(step.to_string());
+    }
+
+    /// The number of deleted objects per type, sorted by type
+    pub fn deleted_counts(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for type_name in self.deleted_ids.lock().values() {
+            *counts.entry(*type_name).or_default() += 1;
+        }
+        counts
+    }
+
     /// Adds a URL pointing to an external resource
-    pub fn add_other_url(&self, url: String) {
-        self.known_other_urls.lock().insert(url);
+    pub fn add_other_url(&self, path: &str, url: String, source: String) {
+        self.known_other_urls
+            .lock()
+            .entry((path.to_string(), url))
+            .or_default()
+            .push(source)
+    }
+
+    /// The number of new problems per severity, most severe first
+    pub fn severity_counts(&self) -> Vec<(Severity, usize)> {
+        let problems = self.problems.lock();
+        [Severity::Error, Severity::Warning, Severity::Info]
+            .into_iter()
+            .map(|severity| {
+                let count = problems
+                    .iter()
+                    .filter(|problem| {
+                        !self.is_known(problem) && self.config.severity(&problem.kind) == severity
+                    })
+                    .count();
+                (severity, count)
+            })
+            .collect()
+    }
+
+    /// The severity of the worst new problem, if there are any
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.problems
+            .lock()
+            .iter()
+            .filter(|problem| !self.is_known(problem))
+            .map(|problem| self.config.severity(&problem.kind))
+            .max()
     }
 
     /// Returns the problems grouped by category for the report generation
     ///
     /// BTreeMap because it uses sorted keys so we get a sorted report later
     pub fn problems_grouped(&self) -> BTreeMap<(ProblemKind, String), Vec<Problem>> {
-        let mut problem_groups = BTreeMap::new();
-        for problem in self.problems.lock().iter() {
-            problem_groups
-                .entry((problem.kind.clone(), problem.path.clone()))
-                .or_insert_with(Vec::new)
-                .push(problem.clone())
-        }
+        group_problems(self.problems.lock().iter())
+    }
+
+    /// Like [Storage::problems_grouped], but either only the problems that are new or only
+    /// those that are already in the baseline
+    pub fn problems_grouped_by_baseline(
+        &self,
+        known: bool,
+    ) -> BTreeMap<(ProblemKind, String), Vec<Problem>> {
+        group_problems(
+            self.problems
+                .lock()
+                .iter()
+                .filter(|problem| self.is_known(problem) == known),
+        )
+    }
+}
+
+fn group_problems<'a>(
+    problems: impl Iterator<Item = &'a Problem>,
+) -> BTreeMap<(ProblemKind, String), Vec<Problem>> {
+    let mut problem_groups = BTreeMap::new();
+    for problem in problems {
         problem_groups
+            .entry((problem.kind.clone(), problem.path.clone()))
+            .or_insert_with(Vec::new)
+            .push(problem.clone())
     }
+    problem_groups
 }
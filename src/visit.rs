@@ -1,16 +1,25 @@
+use crate::checkpoint::deserialize_static;
+use crate::lang::{tr, Lang};
 use crate::reporter::Reporter;
-use crate::schema::{OparlUrl, OtherUrl};
+use crate::schema::{Date, DateTime, OparlUrl, OtherUrl};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Problem {
     pub kind: ProblemKind,
     pub path: String,
     pub detail: String,
+    /// The url of the page or object where the problem was found
+    pub url: Option<String>,
+    /// The id of the affected object, if there is one
+    pub id: Option<String>,
 }
 
 impl Display for Problem {
@@ -23,7 +32,36 @@ impl Display for Problem {
     }
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+/// How bad a problem is, ordered from least to most severe
+#[derive(
+    Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, but not a violation of the spec
+    Info,
+    /// Allowed by the spec, but likely to cause problems for clients
+    Warning,
+    /// A violation of the spec
+    Error,
+}
+
+impl Severity {
+    /// The label used in the text reports
+    pub fn label(&self, lang: Lang) -> &'static str {
+        match self {
+            Severity::Info => lang.pick("Hinweis", "Info"),
+            Severity::Warning => lang.pick("Warnung", "Warning"),
+            Severity::Error => lang.pick("Fehler", "Error"),
+        }
+    }
+}
+
+/// A `&'static str` that serde doesn't borrow from the input, which would only work for input
+/// that lives forever. These are read back from checkpoints through [deserialize_static]
+type StaticStr = &'static str;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ProblemKind {
     /// reqwest error (retries exceeded)
     HttpRequestFailedTooOften,
@@ -31,10 +69,10 @@ pub enum ProblemKind {
     HttpRequestFailedRetrying,
     /// An object that was expected to be in an external list was not part of any list
     /// and could not be fetched manually
-    ObjectMissing(&'static str),
+    ObjectMissing(#[serde(deserialize_with = "deserialize_static")] StaticStr),
     /// An object that was expected to be in an external list was not part of any list,
     /// but could be fetched manually
-    ObjectNotInList(&'static str),
+    ObjectNotInList(#[serde(deserialize_with = "deserialize_static")] StaticStr),
     /// A field required by the oparl spec is missing
     RequiredFieldMissing,
     /// If a value is missing, either the field should be omitted or be `null`, not ""
@@ -43,41 +81,542 @@ pub enum ProblemKind {
     UsingHttp,
     /// serde error
     InvalidJson,
+    /// A single field doesn't match the type from the schema, the rest of the object is still used
+    WrongType,
+    /// A `date` or `date-time` field that can't be parsed
+    InvalidDate,
+    /// A `date-time` without `Z` or an offset such as `+01:00`
+    DateTimeMissingTimezone,
+    /// A `date-time` field that only contains a date
+    DateInsteadOfDateTime,
+    /// The `type` of an object is not the schema url of the type expected at that place
+    WrongObjectType(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// An object links another one, but the linked object doesn't link back in the given field
+    MissingBacklink(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// `pagination.totalElements` doesn't match the number of objects in the list
+    PaginationTotalElements,
+    /// `pagination.totalPages` doesn't match the number of pages in the list
+    PaginationTotalPages,
+    /// `pagination.currentPage` doesn't increase by one from page to page
+    PaginationCurrentPage,
+    /// `pagination.elementsPerPage` doesn't match the number of objects on a page
+    PaginationElementsPerPage,
+    /// `links.first`, `links.self` or `links.last` is missing
+    ListLinkMissing(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// `links.first`, `links.self` or `links.last` points to the wrong page
+    ListLinkWrong(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// `links.next` points to a page we've already seen
+    PaginationLoop,
+    /// The same object is on more than one page of a list
+    DuplicateObjectInList(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// The list couldn't be loaded with the given filter
+    FilterRequestFailed(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// The list with the given filter contains objects that don't match the filter
+    FilterWrongObjects(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// The list with the given filter lacks objects that match the filter
+    FilterMissingObjects(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// The first page of a list has more objects than requested with `limit`
+    FilterLimitIgnored,
+    /// `File.accessUrl` can't be loaded
+    BrokenAccessUrl,
+    /// `File.downloadUrl` can't be loaded
+    BrokenDownloadUrl,
+    /// A `web` or `website` page can't be loaded
+    DeadWebPage,
+    /// A `license` or `fileLicense` url can't be loaded
+    UnreachableLicense,
+    /// An object in a list differs from the same object fetched from its id
+    ListObjectDiffers(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// A deleted object has fields other than `id`, `type`, `created`, `modified` and `deleted`
+    DeletedObjectHasPayload,
+    /// A deleted object has no `modified`, so clients can't know when it was deleted
+    DeletedObjectWithoutModified,
+    /// A live object links to a deleted object of the given type
+    LinkToDeletedObject(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// A field with a namespace prefix such as `ris:status`, which the spec allows for extensions
+    VendorField,
+    /// A field that isn't in the spec but looks like a typo of the given spec field
+    MisspelledField(#[serde(deserialize_with = "deserialize_static")] StaticStr),
+    /// A field that isn't in the spec and has no namespace prefix
+    UnknownField,
+    /// A response has neither `ETag` nor `Last-Modified`, so clients must always load it again
+    NoCacheValidators,
+    /// The server sent an unchanged response again instead of answering a conditional request
+    /// with 304 Not Modified
+    ConditionalRequestIgnored,
 }
 
 impl ProblemKind {
-    pub fn message(&self, detail: &str) -> String {
+    /// The variant name without the object type, used as identifier in the machine-readable reports
+    pub const fn name(&self) -> &'static str {
         match self {
-            ProblemKind::RequiredFieldMissing => {
-                format!("Das zwingend vorgeschriebene Feld {} fehlt", detail)
-            }
-            ProblemKind::ObjectMissing(object_type) => {
-                format!("Das Objekt vom Typ {} wurden von einem anderen Objekt verlinkt, ist aber nicht abrufbar", object_type)
-            }
-            ProblemKind::ObjectNotInList(object_type) => {
-                format!("Das Objekt von Typ {} wurde von einem anderen Objekt verlinkt, fehlt aber in den externen Listen", object_type)
-            }
-            ProblemKind::EmptyString => {
-                format!("Das Feld {} ist angegeben, hat aber keinen Inhalt", detail)
-            }
-            ProblemKind::UsingHttp => "Das unsichere HTTP wird verwendet".to_string(),
-            ProblemKind::InvalidJson => "Es wurde kein gültiges JSON zurückgegeben".to_string(),
-            ProblemKind::HttpRequestFailedRetrying => {
-                "Die Abfrage schlug fehl und musste wiederholt werden".to_string()
-            }
-            ProblemKind::HttpRequestFailedTooOften => {
-                "Die Abfrage schlug zu oft fehl und konnte nicht abgeschlossen werden".to_string()
-            }
+            ProblemKind::HttpRequestFailedTooOften => "HttpRequestFailedTooOften",
+            ProblemKind::HttpRequestFailedRetrying => "HttpRequestFailedRetrying",
+            ProblemKind::ObjectMissing(_) => "ObjectMissing",
+            ProblemKind::ObjectNotInList(_) => "ObjectNotInList",
+            ProblemKind::RequiredFieldMissing => "RequiredFieldMissing",
+            ProblemKind::EmptyString => "EmptyString",
+            ProblemKind::UsingHttp => "UsingHttp",
+            ProblemKind::InvalidJson => "InvalidJson",
+            ProblemKind::WrongType => "WrongType",
+            ProblemKind::InvalidDate => "InvalidDate",
+            ProblemKind::DateTimeMissingTimezone => "DateTimeMissingTimezone",
+            ProblemKind::DateInsteadOfDateTime => "DateInsteadOfDateTime",
+            ProblemKind::WrongObjectType(_) => "WrongObjectType",
+            ProblemKind::MissingBacklink(_) => "MissingBacklink",
+            ProblemKind::PaginationTotalElements => "PaginationTotalElements",
+            ProblemKind::PaginationTotalPages => "PaginationTotalPages",
+            ProblemKind::PaginationCurrentPage => "PaginationCurrentPage",
+            ProblemKind::PaginationElementsPerPage => "PaginationElementsPerPage",
+            ProblemKind::ListLinkMissing(_) => "ListLinkMissing",
+            ProblemKind::ListLinkWrong(_) => "ListLinkWrong",
+            ProblemKind::PaginationLoop => "PaginationLoop",
+            ProblemKind::DuplicateObjectInList(_) => "DuplicateObjectInList",
+            ProblemKind::FilterRequestFailed(_) => "FilterRequestFailed",
+            ProblemKind::FilterWrongObjects(_) => "FilterWrongObjects",
+            ProblemKind::FilterMissingObjects(_) => "FilterMissingObjects",
+            ProblemKind::FilterLimitIgnored => "FilterLimitIgnored",
+            ProblemKind::BrokenAccessUrl => "BrokenAccessUrl",
+            ProblemKind::BrokenDownloadUrl => "BrokenDownloadUrl",
+            ProblemKind::DeadWebPage => "DeadWebPage",
+            ProblemKind::UnreachableLicense => "UnreachableLicense",
+            ProblemKind::ListObjectDiffers(_) => "ListObjectDiffers",
+            ProblemKind::DeletedObjectHasPayload => "DeletedObjectHasPayload",
+            ProblemKind::DeletedObjectWithoutModified => "DeletedObjectWithoutModified",
+            ProblemKind::LinkToDeletedObject(_) => "LinkToDeletedObject",
+            ProblemKind::VendorField => "VendorField",
+            ProblemKind::MisspelledField(_) => "MisspelledField",
+            ProblemKind::UnknownField => "UnknownField",
+            ProblemKind::NoCacheValidators => "NoCacheValidators",
+            ProblemKind::ConditionalRequestIgnored => "ConditionalRequestIgnored",
+        }
+    }
+
+    /// The stable code and the name of each kind
+    ///
+    /// The codes are used to select and ignore rules, so they must never be reused or renumbered.
+    /// New kinds get the next free number, in both [ProblemKind::code] and this list.
+    pub const RULES: [(&'static str, &'static str); 39] = [
+        ("OPARL001", "HttpRequestFailedTooOften"),
+        ("OPARL002", "HttpRequestFailedRetrying"),
+        ("OPARL003", "ObjectMissing"),
+        ("OPARL004", "ObjectNotInList"),
+        ("OPARL005", "RequiredFieldMissing"),
+        ("OPARL006", "EmptyString"),
+        ("OPARL007", "UsingHttp"),
+        ("OPARL008", "InvalidJson"),
+        ("OPARL009", "WrongType"),
+        ("OPARL010", "InvalidDate"),
+        ("OPARL011", "DateTimeMissingTimezone"),
+        ("OPARL012", "DateInsteadOfDateTime"),
+        ("OPARL013", "WrongObjectType"),
+        ("OPARL014", "MissingBacklink"),
+        ("OPARL015", "PaginationTotalElements"),
+        ("OPARL016", "PaginationTotalPages"),
+        ("OPARL017", "PaginationCurrentPage"),
+        ("OPARL018", "PaginationElementsPerPage"),
+        ("OPARL019", "ListLinkMissing"),
+        ("OPARL020", "ListLinkWrong"),
+        ("OPARL021", "PaginationLoop"),
+        ("OPARL022", "DuplicateObjectInList"),
+        ("OPARL023", "FilterRequestFailed"),
+        ("OPARL024", "FilterWrongObjects"),
+        ("OPARL025", "FilterMissingObjects"),
+        ("OPARL026", "FilterLimitIgnored"),
+        ("OPARL027", "BrokenAccessUrl"),
+        ("OPARL028", "BrokenDownloadUrl"),
+        ("OPARL029", "DeadWebPage"),
+        ("OPARL030", "UnreachableLicense"),
+        ("OPARL031", "ListObjectDiffers"),
+        ("OPARL032", "DeletedObjectHasPayload"),
+        ("OPARL033", "DeletedObjectWithoutModified"),
+        ("OPARL034", "LinkToDeletedObject"),
+        ("OPARL035", "VendorField"),
+        ("OPARL036", "MisspelledField"),
+        ("OPARL037", "UnknownField"),
+        ("OPARL038", "NoCacheValidators"),
+        ("OPARL039", "ConditionalRequestIgnored"),
+    ];
+
+    /// Looks up the code and the name of a rule given by code (e.g. `OPARL007`) or name
+    /// (e.g. `UsingHttp`)
+    pub fn find_rule(rule: &str) -> Option<(&'static str, &'static str)> {
+        ProblemKind::RULES
+            .iter()
+            .find(|(code, name)| code.eq_ignore_ascii_case(rule) || *name == rule)
+            .copied()
+    }
+
+    /// The stable code, e.g. `OPARL007` for [ProblemKind::UsingHttp]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProblemKind::HttpRequestFailedTooOften => "OPARL001",
+            ProblemKind::HttpRequestFailedRetrying => "OPARL002",
+            ProblemKind::ObjectMissing(_) => "OPARL003",
+            ProblemKind::ObjectNotInList(_) => "OPARL004",
+            ProblemKind::RequiredFieldMissing => "OPARL005",
+            ProblemKind::EmptyString => "OPARL006",
+            ProblemKind::UsingHttp => "OPARL007",
+            ProblemKind::InvalidJson => "OPARL008",
+            ProblemKind::WrongType => "OPARL009",
+            ProblemKind::InvalidDate => "OPARL010",
+            ProblemKind::DateTimeMissingTimezone => "OPARL011",
+            ProblemKind::DateInsteadOfDateTime => "OPARL012",
+            ProblemKind::WrongObjectType(_) => "OPARL013",
+            ProblemKind::MissingBacklink(_) => "OPARL014",
+            ProblemKind::PaginationTotalElements => "OPARL015",
+            ProblemKind::PaginationTotalPages => "OPARL016",
+            ProblemKind::PaginationCurrentPage => "OPARL017",
+            ProblemKind::PaginationElementsPerPage => "OPARL018",
+            ProblemKind::ListLinkMissing(_) => "OPARL019",
+            ProblemKind::ListLinkWrong(_) => "OPARL020",
+            ProblemKind::PaginationLoop => "OPARL021",
+            ProblemKind::DuplicateObjectInList(_) => "OPARL022",
+            ProblemKind::FilterRequestFailed(_) => "OPARL023",
+            ProblemKind::FilterWrongObjects(_) => "OPARL024",
+            ProblemKind::FilterMissingObjects(_) => "OPARL025",
+            ProblemKind::FilterLimitIgnored => "OPARL026",
+            ProblemKind::BrokenAccessUrl => "OPARL027",
+            ProblemKind::BrokenDownloadUrl => "OPARL028",
+            ProblemKind::DeadWebPage => "OPARL029",
+            ProblemKind::UnreachableLicense => "OPARL030",
+            ProblemKind::ListObjectDiffers(_) => "OPARL031",
+            ProblemKind::DeletedObjectHasPayload => "OPARL032",
+            ProblemKind::DeletedObjectWithoutModified => "OPARL033",
+            ProblemKind::LinkToDeletedObject(_) => "OPARL034",
+            ProblemKind::VendorField => "OPARL035",
+            ProblemKind::MisspelledField(_) => "OPARL036",
+            ProblemKind::UnknownField => "OPARL037",
+            ProblemKind::NoCacheValidators => "OPARL038",
+            ProblemKind::ConditionalRequestIgnored => "OPARL039",
+        }
+    }
+
+    /// The severity unless it's overridden in the config
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            ProblemKind::HttpRequestFailedTooOften
+            | ProblemKind::ObjectMissing(_)
+            | ProblemKind::RequiredFieldMissing
+            | ProblemKind::InvalidJson
+            | ProblemKind::WrongType
+            | ProblemKind::InvalidDate
+            | ProblemKind::WrongObjectType(_)
+            | ProblemKind::PaginationLoop
+            | ProblemKind::FilterRequestFailed(_)
+            | ProblemKind::FilterWrongObjects(_)
+            | ProblemKind::FilterMissingObjects(_)
+            | ProblemKind::BrokenAccessUrl
+            | ProblemKind::DeletedObjectWithoutModified => Severity::Error,
+            ProblemKind::ObjectNotInList(_)
+            | ProblemKind::EmptyString
+            | ProblemKind::UsingHttp
+            | ProblemKind::DateTimeMissingTimezone
+            | ProblemKind::DateInsteadOfDateTime
+            | ProblemKind::MissingBacklink(_)
+            | ProblemKind::PaginationTotalElements
+            | ProblemKind::PaginationTotalPages
+            | ProblemKind::PaginationCurrentPage
+            | ProblemKind::PaginationElementsPerPage
+            | ProblemKind::ListLinkMissing(_)
+            | ProblemKind::ListLinkWrong(_)
+            | ProblemKind::DuplicateObjectInList(_)
+            | ProblemKind::FilterLimitIgnored
+            | ProblemKind::BrokenDownloadUrl
+            | ProblemKind::DeadWebPage
+            | ProblemKind::UnreachableLicense
+            | ProblemKind::ListObjectDiffers(_)
+            | ProblemKind::DeletedObjectHasPayload
+            | ProblemKind::LinkToDeletedObject(_)
+            | ProblemKind::MisspelledField(_)
+            | ProblemKind::UnknownField => Severity::Warning,
+            ProblemKind::HttpRequestFailedRetrying
+            | ProblemKind::VendorField
+            | ProblemKind::NoCacheValidators
+            | ProblemKind::ConditionalRequestIgnored => Severity::Info,
+        }
+    }
+
+    /// The oparl type the problem is about, for the kinds that have one
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            ProblemKind::ObjectMissing(type_name)
+            | ProblemKind::ObjectNotInList(type_name)
+            | ProblemKind::WrongObjectType(type_name)
+            | ProblemKind::DuplicateObjectInList(type_name)
+            | ProblemKind::ListObjectDiffers(type_name)
+            | ProblemKind::LinkToDeletedObject(type_name) => Some(type_name),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self, detail: &str, lang: Lang) -> String {
+        match self {
+            ProblemKind::RequiredFieldMissing => tr!(
+                lang,
+                "Das zwingend vorgeschriebene Feld {} fehlt",
+                "The required field {} is missing",
+                detail
+            ),
+            ProblemKind::ObjectMissing(object_type) => tr!(
+                lang,
+                "Das Objekt vom Typ {} wurden von einem anderen Objekt verlinkt, ist aber nicht abrufbar",
+                "The object of type {} is linked by another object, but can't be retrieved",
+                object_type
+            ),
+            ProblemKind::ObjectNotInList(object_type) => tr!(
+                lang,
+                "Das Objekt von Typ {} wurde von einem anderen Objekt verlinkt, fehlt aber in den externen Listen",
+                "The object of type {} is linked by another object, but is missing from the external lists",
+                object_type
+            ),
+            ProblemKind::EmptyString => tr!(
+                lang,
+                "Das Feld {} ist angegeben, hat aber keinen Inhalt",
+                "The field {} is set, but empty",
+                detail
+            ),
+            ProblemKind::UsingHttp => tr!(
+                lang,
+                "Das unsichere HTTP wird verwendet",
+                "The insecure HTTP is used"
+            ),
+            ProblemKind::InvalidJson => tr!(
+                lang,
+                "Es wurde kein gültiges JSON zurückgegeben",
+                "The response is not valid JSON"
+            ),
+            ProblemKind::WrongType => tr!(
+                lang,
+                "Das Feld {} hat den falschen Datentyp",
+                "The field {} has the wrong data type",
+                detail
+            ),
+            ProblemKind::InvalidDate => tr!(
+                lang,
+                "Das Feld {} enthält kein gültiges Datum",
+                "The field {} doesn't contain a valid date",
+                detail
+            ),
+            ProblemKind::DateTimeMissingTimezone => tr!(
+                lang,
+                "Der Zeitpunkt im Feld {} hat keine Zeitzone",
+                "The date-time in the field {} has no timezone",
+                detail
+            ),
+            ProblemKind::DateInsteadOfDateTime => tr!(
+                lang,
+                "Das Feld {} enthält nur ein Datum, erwartet wird ein Zeitpunkt mit Uhrzeit",
+                "The field {} only contains a date, but a date-time is expected",
+                detail
+            ),
+            ProblemKind::WrongObjectType(object_type) => tr!(
+                lang,
+                "Das Feld {} passt nicht zum erwarteten Typ {}",
+                "The field {} doesn't match the expected type {}",
+                detail,
+                object_type
+            ),
+            ProblemKind::MissingBacklink(backlink) => tr!(
+                lang,
+                "Das Feld {} verlinkt ein Objekt, das im Feld {} nicht zurück verlinkt",
+                "The field {} links an object that doesn't link back in the field {}",
+                detail,
+                backlink
+            ),
+            ProblemKind::PaginationTotalElements => tr!(
+                lang,
+                "Die Gesamtzahl der Objekte in pagination.totalElements stimmt nicht mit der Liste überein",
+                "The total number of objects in pagination.totalElements doesn't match the list"
+            ),
+            ProblemKind::PaginationTotalPages => tr!(
+                lang,
+                "Die Seitenzahl in pagination.totalPages stimmt nicht mit der Liste überein",
+                "The number of pages in pagination.totalPages doesn't match the list"
+            ),
+            ProblemKind::PaginationCurrentPage => tr!(
+                lang,
+                "Die Seitennummer in pagination.currentPage steigt nicht um eins",
+                "The page number in pagination.currentPage doesn't increase by one"
+            ),
+            ProblemKind::PaginationElementsPerPage => tr!(
+                lang,
+                "Die Anzahl der Objekte auf einer Seite passt nicht zu pagination.elementsPerPage",
+                "The number of objects on a page doesn't match pagination.elementsPerPage"
+            ),
+            ProblemKind::ListLinkMissing(link) => tr!(
+                lang,
+                "Der Link links.{} einer externen Liste fehlt",
+                "The link links.{} of an external list is missing",
+                link
+            ),
+            ProblemKind::ListLinkWrong(link) => tr!(
+                lang,
+                "Der Link links.{} einer externen Liste zeigt auf die falsche Seite",
+                "The link links.{} of an external list points to the wrong page",
+                link
+            ),
+            ProblemKind::PaginationLoop => tr!(
+                lang,
+                "Der next-Link einer externen Liste verweist auf eine frühere Seite, die Liste wurde abgebrochen",
+                "The next link of an external list points to an earlier page, the list was aborted"
+            ),
+            ProblemKind::DuplicateObjectInList(object_type) => tr!(
+                lang,
+                "Ein Objekt vom Typ {} kommt mehrfach in einer externen Liste vor",
+                "An object of type {} occurs multiple times in an external list",
+                object_type
+            ),
+            ProblemKind::FilterRequestFailed(filter) => tr!(
+                lang,
+                "Die Liste der Objekte vom Typ {} kann nicht mit dem Filter {} abgerufen werden",
+                "The list of objects of type {} can't be retrieved with the filter {}",
+                detail,
+                filter
+            ),
+            ProblemKind::FilterWrongObjects(filter) => tr!(
+                lang,
+                "Die Liste der Objekte vom Typ {} enthält mit dem Filter {} Objekte, die nicht zum Filter passen",
+                "With the filter {1}, the list of objects of type {0} contains objects that don't match the filter",
+                detail,
+                filter
+            ),
+            ProblemKind::FilterMissingObjects(filter) => tr!(
+                lang,
+                "Der Liste der Objekte vom Typ {} fehlen mit dem Filter {} passende Objekte",
+                "With the filter {1}, matching objects are missing from the list of objects of type {0}",
+                detail,
+                filter
+            ),
+            ProblemKind::FilterLimitIgnored => tr!(
+                lang,
+                "Die Liste der Objekte vom Typ {} liefert mehr Objekte als mit limit angefordert",
+                "The list of objects of type {} returns more objects than requested with limit",
+                detail
+            ),
+            ProblemKind::BrokenAccessUrl => tr!(
+                lang,
+                "Die Datei im Feld {} kann nicht abgerufen werden",
+                "The file in the field {} can't be retrieved",
+                detail
+            ),
+            ProblemKind::BrokenDownloadUrl => tr!(
+                lang,
+                "Der Download-Link im Feld {} kann nicht abgerufen werden",
+                "The download link in the field {} can't be retrieved",
+                detail
+            ),
+            ProblemKind::DeadWebPage => tr!(
+                lang,
+                "Die Webseite im Feld {} ist nicht erreichbar",
+                "The web page in the field {} is unreachable",
+                detail
+            ),
+            ProblemKind::UnreachableLicense => tr!(
+                lang,
+                "Die Lizenz im Feld {} ist nicht erreichbar",
+                "The license in the field {} is unreachable",
+                detail
+            ),
+            ProblemKind::ListObjectDiffers(object_type) => tr!(
+                lang,
+                "Ein Objekt vom Typ {} unterscheidet sich im Feld {} zwischen der Liste und dem Abruf über seine id",
+                "An object of type {} differs in the field {} between the list and retrieving it by its id",
+                object_type,
+                detail
+            ),
+            ProblemKind::DeletedObjectHasPayload => tr!(
+                lang,
+                "Das gelöschte Objekt enthält noch das Feld {}, erlaubt sind nur id, type, created, modified und deleted",
+                "The deleted object still contains the field {}, only id, type, created, modified and deleted are allowed",
+                detail
+            ),
+            ProblemKind::DeletedObjectWithoutModified => tr!(
+                lang,
+                "Dem gelöschten Objekt fehlt das Feld {} mit dem Zeitpunkt der Löschung",
+                "The deleted object is missing the field {} with the time of deletion",
+                detail
+            ),
+            ProblemKind::LinkToDeletedObject(object_type) => tr!(
+                lang,
+                "Das Feld {} verlinkt ein gelöschtes Objekt vom Typ {}",
+                "The field {} links a deleted object of type {}",
+                detail,
+                object_type
+            ),
+            ProblemKind::VendorField => tr!(
+                lang,
+                "Das Feld {} ist eine herstellerspezifische Erweiterung",
+                "The field {} is a vendor extension",
+                detail
+            ),
+            ProblemKind::MisspelledField(field) => tr!(
+                lang,
+                "Das Feld {} ist nicht im Standard definiert, gemeint ist vermutlich {}",
+                "The field {} is not defined in the spec, {} is probably meant",
+                detail,
+                field
+            ),
+            ProblemKind::UnknownField => tr!(
+                lang,
+                "Das Feld {} ist nicht im Standard definiert und nicht als herstellerspezifische Erweiterung (z.B. ris:feld) gekennzeichnet",
+                "The field {} is not defined in the spec and not marked as a vendor extension (e.g. ris:field)",
+                detail
+            ),
+            ProblemKind::NoCacheValidators => tr!(
+                lang,
+                "Die Antwort hat weder ETag noch Last-Modified und kann daher nicht bedingt abgefragt werden",
+                "The response has neither ETag nor Last-Modified, so it can't be requested conditionally"
+            ),
+            ProblemKind::ConditionalRequestIgnored => tr!(
+                lang,
+                "Der Server ignoriert If-None-Match und If-Modified-Since und sendet die unveränderte Antwort erneut",
+                "The server ignores If-None-Match and If-Modified-Since and sends the unchanged response again"
+            ),
+            ProblemKind::HttpRequestFailedRetrying => tr!(
+                lang,
+                "Die Abfrage schlug fehl und musste wiederholt werden",
+                "The request failed and had to be retried"
+            ),
+            ProblemKind::HttpRequestFailedTooOften => tr!(
+                lang,
+                "Die Abfrage schlug zu oft fehl und konnte nicht abgeschlossen werden",
+                "The request failed too often and couldn't be completed"
+            ),
         }
     }
 }
 
+/// The schema urls of the oparl versions, the object types are appended to them
+pub const OPARL_VERSIONS: [&str; 2] = [
+    "https://schema.oparl.org/1.0/",
+    "https://schema.oparl.org/1.1/",
+];
+
 /// The impls are generated by the python script
-pub trait OparlObject: Send + 'static {
+pub trait OparlObject: Serialize + Send + 'static {
     fn type_name() -> &'static str;
     fn visit_fields(&self, reporter: &impl Reporter, url: &str);
     fn get_required(&self) -> Vec<&str>;
     fn get_id(&self) -> Option<&str>;
+    fn get_type(&self) -> Option<&str>;
+    /// The names of the fields in the spec, spelled as in the json
+    fn field_names() -> &'static [&'static str];
+    /// The fields that aren't in the spec
+    fn get_other(&self) -> &HashMap<String, Value>;
+
+    /// Not all types have `created`, `modified` and `deleted`
+    fn get_created(&self) -> Option<&str> {
+        None
+    }
+
+    fn get_modified(&self) -> Option<&str> {
+        None
+    }
+
+    fn get_deleted(&self) -> bool {
+        false
+    }
 
     fn visit_field<T: Visitable>(
         &self,
@@ -88,9 +627,11 @@ pub trait OparlObject: Send + 'static {
     ) {
         if self.get_required().contains(&name) && !field.is_some() {
             reporter.add_problem(Problem {
-                detail: format_detail(self.get_id(), url),
+                detail: format_detail(self.get_id(), url, reporter.lang()),
                 path: format!("{}.{}", Self::type_name(), name),
                 kind: ProblemKind::RequiredFieldMissing,
+                url: Some(url.to_string()),
+                id: self.get_id().map(ToString::to_string),
             });
         }
         field.visit(
@@ -118,16 +659,139 @@ impl Visitable for bool {}
 /// This one is for the location geojson
 impl Visitable for HashMap<String, Value> {}
 
+/// The number of single character edits to turn `left` into `right`
+fn levenshtein(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != *right_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+/// The spec field that `field` is most likely a typo of, if any
+///
+/// `agendaitem` matches `agendaItem` regardless of the distance, `modfied` matches
+/// `modified` because it's only one edit away
+pub fn closest_field(field: &str, field_names: &[&'static str]) -> Option<&'static str> {
+    if let Some(name) = field_names
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(field))
+    {
+        return Some(name);
+    }
+    // Short names are too similar to each other for a meaningful suggestion
+    let max_distance = (field.chars().count() / 4).min(2);
+    field_names
+        .iter()
+        .map(|name| (levenshtein(field, name), *name))
+        .filter(|(distance, _)| (1..=max_distance).contains(distance))
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Reports the fields that aren't in the spec, telling extensions apart from typos
+fn check_other_fields<T: OparlObject>(object: &T, reporter: &impl Reporter, url: &str) {
+    for field in object.get_other().keys() {
+        let kind = if field.contains(':') {
+            ProblemKind::VendorField
+        } else if let Some(name) = closest_field(field, T::field_names()) {
+            ProblemKind::MisspelledField(name)
+        } else {
+            ProblemKind::UnknownField
+        };
+        reporter.add_problem(Problem {
+            detail: format_detail(object.get_id(), url, reporter.lang()),
+            path: format!("{}.{}", T::type_name(), field),
+            kind,
+            url: Some(url.to_string()),
+            id: object.get_id().map(ToString::to_string),
+        });
+    }
+}
+
+/// The only fields a deleted object may have
+const DELETED_FIELDS: [&str; 5] = ["id", "type", "created", "modified", "deleted"];
+
+/// Checks that a deleted object has only the allowed fields and the time of deletion
+fn check_deleted<T: OparlObject>(object: &T, reporter: &impl Reporter, url: &str) {
+    // Through serde we also get the fields that aren't in the schema
+    let Ok(Value::Object(fields)) = serde_json::to_value(object) else {
+        return;
+    };
+    let add_problem = |kind: ProblemKind, field: &str| {
+        reporter.add_problem(Problem {
+            detail: format_detail(object.get_id(), url, reporter.lang()),
+            path: format!("{}.{}", T::type_name(), field),
+            kind,
+            url: Some(url.to_string()),
+            id: object.get_id().map(ToString::to_string),
+        })
+    };
+    for (field, value) in &fields {
+        if !value.is_null() && !DELETED_FIELDS.contains(&field.as_str()) {
+            add_problem(ProblemKind::DeletedObjectHasPayload, field);
+        }
+    }
+    if fields.get("modified").is_none_or(Value::is_null) {
+        add_problem(ProblemKind::DeletedObjectWithoutModified, "modified");
+    }
+}
+
 impl<T: OparlObject> Visitable for T {
-    fn visit(&self, reporter: &impl Reporter, url: &str, _id: Option<&str>, _path: &str) {
+    fn visit(&self, reporter: &impl Reporter, url: &str, parent_id: Option<&str>, path: &str) {
         if let Some(id) = self.get_id() {
-            reporter.add_id(id.deref().to_string());
+            reporter.add_id(id.to_string());
+            // Embedded objects, the path is empty for the objects directly in a list
+            if let (Some(parent_id), false) = (parent_id, path.is_empty()) {
+                reporter.add_field_link(path, parent_id.to_string(), id.to_string(), true);
+            }
 
             if id.starts_with("http:") {
                 reporter.add_problem(Problem {
-                    detail: format!("Das id Feld verwendet das unsichere http ({})", id),
+                    detail: tr!(
+                        reporter.lang(),
+                        "Das id Feld verwendet das unsichere http ({})",
+                        "The id field uses the insecure http ({})",
+                        id
+                    ),
                     path: String::new(),
                     kind: ProblemKind::UsingHttp,
+                    url: Some(url.to_string()),
+                    id: Some(id.to_string()),
+                });
+            }
+
+            if self.get_deleted() {
+                reporter
+                    .get_storage()
+                    .add_deleted(T::type_name(), id.to_string());
+            }
+        }
+        if self.get_deleted() {
+            check_deleted(self, reporter, url);
+        }
+        check_other_fields(self, reporter, url);
+        if let Some(object_type) = self.get_type() {
+            if !OPARL_VERSIONS
+                .iter()
+                .any(|version| object_type == format!("{}{}", version, T::type_name()))
+            {
+                reporter.add_problem(Problem {
+                    detail: format!(
+                        "{}: {}",
+                        format_detail(self.get_id(), url, reporter.lang()),
+                        object_type
+                    ),
+                    path: format!("{}.type", T::type_name()),
+                    kind: ProblemKind::WrongObjectType(T::type_name()),
+                    url: Some(url.to_string()),
+                    id: self.get_id().map(ToString::to_string),
                 });
             }
         }
@@ -147,30 +811,82 @@ impl Visitable for String {
     fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
         if self.is_empty() {
             reporter.add_problem(Problem {
-                detail: format_detail(id, url),
+                detail: format_detail(id, url, reporter.lang()),
                 path: path.to_string(),
                 kind: ProblemKind::EmptyString,
+                url: Some(url.to_string()),
+                id: id.map(ToString::to_string),
             })
         }
     }
 }
 
 impl<T: OparlObject> Visitable for OparlUrl<T> {
-    fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, _path: &str) {
+    fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
         reporter.add_link(
             T::type_name(),
             self.deref().clone(),
             id.unwrap_or(url).to_string(),
         );
+        if let Some(id) = id {
+            reporter.add_field_link(path, id.to_string(), self.deref().clone(), false);
+        }
     }
 }
 
 impl Visitable for OtherUrl {
-    fn visit(&self, reporter: &impl Reporter, _url: &str, _id: Option<&str>, _path: &str) {
-        reporter.add_other_url(self.deref().clone());
+    fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
+        reporter.add_other_url(path, self.deref().clone(), id.unwrap_or(url).to_string());
+    }
+}
+
+impl Visitable for Date {
+    fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
+        if self.is_empty() {
+            return self.deref().visit(reporter, url, id, path);
+        }
+        if NaiveDate::parse_from_str(self, "%Y-%m-%d").is_err() {
+            add_date_problem(reporter, url, id, path, self, ProblemKind::InvalidDate);
+        }
     }
 }
 
+impl Visitable for DateTime {
+    fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
+        if self.is_empty() {
+            return self.deref().visit(reporter, url, id, path);
+        }
+        if chrono::DateTime::parse_from_rfc3339(self).is_ok() {
+            return;
+        }
+        let kind = if NaiveDateTime::parse_from_str(self, "%Y-%m-%dT%H:%M:%S%.f").is_ok() {
+            ProblemKind::DateTimeMissingTimezone
+        } else if NaiveDate::parse_from_str(self, "%Y-%m-%d").is_ok() {
+            ProblemKind::DateInsteadOfDateTime
+        } else {
+            ProblemKind::InvalidDate
+        };
+        add_date_problem(reporter, url, id, path, self, kind);
+    }
+}
+
+fn add_date_problem(
+    reporter: &impl Reporter,
+    url: &str,
+    id: Option<&str>,
+    path: &str,
+    value: &str,
+    kind: ProblemKind,
+) {
+    reporter.add_problem(Problem {
+        detail: format!("{}: {}", format_detail(id, url, reporter.lang()), value),
+        path: path.to_string(),
+        kind,
+        url: Some(url.to_string()),
+        id: id.map(ToString::to_string),
+    })
+}
+
 impl<T: Visitable> Visitable for Option<T> {
     fn visit(&self, reporter: &impl Reporter, url: &str, id: Option<&str>, path: &str) {
         self.as_ref()
@@ -183,13 +899,15 @@ impl<T: Visitable> Visitable for Option<T> {
     }
 }
 
-pub fn format_detail(id: Option<&str>, url: &str) -> String {
+pub fn format_detail(id: Option<&str>, url: &str, lang: Lang) -> String {
     if id == Some(url) {
         url.to_string()
     } else {
-        format!(
+        tr!(
+            lang,
             "{} innerhalb von {}",
-            id.unwrap_or("object with missing id"),
+            "{} within {}",
+            id.unwrap_or(lang.pick("Objekt ohne id", "object with missing id")),
             url
         )
     }
@@ -1,11 +1,14 @@
 #![cfg_attr(not(target_family = "wasm"), allow(clippy::unused_unit))]
 
 use crate::client::{NoCache, OparlClient};
+use crate::config::Config;
 use crate::endpoints::EndpointsYmlEntry;
+use crate::lang::{tr, Lang};
 use crate::reporter::Reporter;
 use crate::storage::Storage;
-use crate::{validate_oparl_api, Cache, Problem, ProgressBarWrapper};
+use crate::{validate_oparl_api, Cache, Problem, ProgressBarWrapper, ValidationOptions};
 use anyhow::{format_err, Context, Result};
+use clap::ValueEnum;
 use parking_lot::Mutex;
 use serde::Serialize;
 use std::borrow::Cow;
@@ -43,15 +46,32 @@ pub enum WasmMessage {
     },
 }
 
+/// `lang` is the language of the messages, `de` or `en`
 #[wasm_bindgen]
-pub async fn test_city(city_or_url: String, callback: MessageChannel) -> Result<(), JsValue> {
+pub async fn test_city(
+    city_or_url: String,
+    lang: String,
+    callback: MessageChannel,
+) -> Result<(), JsValue> {
+    let lang = Lang::from_str(&lang, true).map_err(|err| JsValue::from_str(&err))?;
+    let config = Config {
+        lang,
+        ..Config::default()
+    };
     let callback = callback.port2();
-    let reporter = ReporterWasm::new(Storage::new(), callback.clone());
+    let reporter = ReporterWasm::new(Storage::with_config(config), callback.clone());
     // Not using LocalStorage because of quota and old cache issue
-    validate_oparl_api::<ReporterWasm, _>(&city_or_url, &reporter, &OparlClient::new(NoCache))
-        .await
-        .unwrap();
-    console::log_1(&JsValue::from_str(&format!(
+    validate_oparl_api::<ReporterWasm, _>(
+        &city_or_url,
+        &reporter,
+        &OparlClient::new(NoCache),
+        &ValidationOptions::default(),
+    )
+    .await
+    .unwrap();
+    console::log_1(&JsValue::from_str(&tr!(
+        lang,
+        "{} Probleme gefunden",
         "Found {} problems",
         reporter.get_storage().problems.lock().len()
     )));
@@ -107,19 +127,47 @@ impl ReporterWasm {
     }
 
     pub fn report_problems(&self, is_final: bool) {
+        let lang = self.lang();
         let problem_groups = self.get_storage().problems_grouped();
-        let problems = problem_groups
+        let mut problems: Vec<String> = problem_groups
             .iter()
             .map(|(group, problems)| {
-                format!(
-                    "{} ({} Fälle). Beispiel: {}",
-                    group.0.message(&group.1),
+                tr!(
+                    lang,
+                    "[{}] {} ({} Fälle). Beispiel: {}",
+                    "[{}] {} ({} cases). Example: {}",
+                    group.0.code(),
+                    group.0.message(&group.1, lang),
                     problems.len(),
                     // All vecs are non-empty
                     problems.iter().min().unwrap().detail
                 )
             })
             .collect();
+        let severity_counts: Vec<String> = self
+            .get_storage()
+            .severity_counts()
+            .into_iter()
+            .map(|(severity, count)| {
+                tr!(
+                    lang,
+                    "{}: {} Fälle",
+                    "{}: {} cases",
+                    severity.label(lang),
+                    count
+                )
+            })
+            .collect();
+        problems.push(severity_counts.join(", "));
+        for (type_name, count) in self.get_storage().deleted_counts() {
+            problems.push(tr!(
+                lang,
+                "Gelöschte Objekte vom Typ {}: {}",
+                "Deleted objects of type {}: {}",
+                type_name,
+                count
+            ));
+        }
 
         self.callback
             .post_message(
@@ -0,0 +1,87 @@
+//! Helpers shared by the integration tests, each test file only uses some of them
+#![allow(dead_code)]
+
+use oparl_validator_rs::visit::{Problem, ProblemKind};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// A problem with the object id as detail, as most checks report it
+pub fn problem(kind: ProblemKind, path: &str, id: Option<&str>) -> Problem {
+    Problem {
+        kind,
+        path: path.to_string(),
+        detail: id.unwrap_or_default().to_string(),
+        url: None,
+        id: id.map(ToString::to_string),
+    }
+}
+
+/// A request to the server from [serve]
+pub struct Request {
+    /// E.g. `GET /paper/1 HTTP/1.1`
+    pub line: String,
+    /// By lowercase name
+    pub headers: HashMap<String, String>,
+}
+
+/// The answer of the server from [serve]
+pub struct Response {
+    /// E.g. `404 Not Found`
+    pub status: &'static str,
+    /// E.g. `ETag: "1"`
+    pub headers: Vec<String>,
+    pub body: &'static str,
+}
+
+impl Response {
+    pub fn status(status: &'static str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: "",
+        }
+    }
+}
+
+/// Starts an http server on a free port that answers every request with `respond`, returns
+/// its address
+pub fn serve(respond: impl Fn(&Request) -> Response + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut request = Request {
+                line: line.trim_end().to_string(),
+                headers: HashMap::new(),
+            };
+            line.clear();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                if let Some((name, value)) = line.split_once(':') {
+                    let value = value.trim().to_string();
+                    request.headers.insert(name.to_lowercase(), value);
+                }
+                line.clear();
+            }
+            let response = respond(&request);
+            let mut head = format!("HTTP/1.1 {}\r\n", response.status);
+            for header in response.headers {
+                head += &format!("{}\r\n", header);
+            }
+            write!(
+                stream,
+                "{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                head,
+                response.body.len(),
+                response.body
+            )
+            .unwrap();
+        }
+    });
+    address
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use oparl_validator_rs::baseline::Baseline;
+use oparl_validator_rs::cli::problem_report;
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::{Problem, ProblemKind, Severity};
+
+mod common;
+use common::problem;
+
+#[test]
+fn test_baseline() -> Result<()> {
+    let old_run = Storage::new();
+    old_run.add_problem(problem(
+        ProblemKind::RequiredFieldMissing,
+        "Paper.type",
+        Some("https://example.org/paper/1"),
+    ));
+    old_run.add_problem(problem(
+        ProblemKind::EmptyString,
+        "Paper.name",
+        Some("https://example.org/paper/2"),
+    ));
+    let mut baseline_file = Vec::new();
+    Baseline::from_storage(&old_run).write(&mut baseline_file)?;
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("baseline.json");
+    fs_err::write(&path, baseline_file)?;
+
+    let new_run = Storage::new().with_baseline(Baseline::load(&path)?);
+    // Still there
+    new_run.add_problem(problem(
+        ProblemKind::RequiredFieldMissing,
+        "Paper.type",
+        Some("https://example.org/paper/1"),
+    ));
+    // Same kind and path, but another object
+    new_run.add_problem(problem(
+        ProblemKind::RequiredFieldMissing,
+        "Paper.type",
+        Some("https://example.org/paper/3"),
+    ));
+    assert_eq!(new_run.max_severity(), Some(Severity::Error));
+
+    let mut out = Vec::new();
+    let mut report = Vec::new();
+    problem_report(&new_run, &mut out, &mut report, true)?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        "=== Validierungsreport: Zusammenfassung der neuen Probleme ===
+[OPARL005] Das zwingend vorgeschriebene Feld Paper.type fehlt (1 Fälle). Beispiel: https://example.org/paper/3
+Fehler: 1 Fälle, Warnung: 0 Fälle, Hinweis: 0 Fälle
+=== Bekannte Probleme aus der Baseline ===
+[OPARL005] Das zwingend vorgeschriebene Feld Paper.type fehlt (1 Fälle). Beispiel: https://example.org/paper/1
+"
+    );
+
+    // Only known problems don't fail the run
+    let fixed_run = Storage::new().with_baseline(Baseline::load(&path)?);
+    fixed_run.add_problem(problem(
+        ProblemKind::EmptyString,
+        "Paper.name",
+        Some("https://example.org/paper/2"),
+    ));
+    assert_eq!(fixed_run.max_severity(), None);
+    Ok(())
+}
+
+#[test]
+fn test_baseline_key() {
+    let old_run = Storage::new();
+    old_run.add_problem(problem(
+        ProblemKind::ObjectMissing("Meeting"),
+        "",
+        Some("https://example.org/meeting/1"),
+    ));
+    old_run.add_problem(Problem {
+        kind: ProblemKind::UsingHttp,
+        path: String::new(),
+        detail: String::new(),
+        url: Some("http://example.org/paper".to_string()),
+        id: None,
+    });
+
+    let new_run = Storage::new().with_baseline(Baseline::from_storage(&old_run));
+    // Another type with the same id is a new problem
+    new_run.add_problem(problem(
+        ProblemKind::ObjectMissing("Person"),
+        "",
+        Some("https://example.org/meeting/1"),
+    ));
+    assert_eq!(new_run.max_severity(), Some(Severity::Error));
+
+    // Without an id, the url tells the problems apart
+    let new_run = Storage::new().with_baseline(Baseline::from_storage(&old_run));
+    new_run.add_problem(Problem {
+        kind: ProblemKind::UsingHttp,
+        path: String::new(),
+        detail: String::new(),
+        url: Some("http://example.org/paper".to_string()),
+        id: None,
+    });
+    assert_eq!(new_run.max_severity(), None);
+    new_run.add_problem(Problem {
+        kind: ProblemKind::UsingHttp,
+        path: String::new(),
+        detail: String::new(),
+        url: Some("http://example.org/person".to_string()),
+        id: None,
+    });
+    assert!(new_run.max_severity().is_some());
+}
@@ -0,0 +1,17 @@
+use oparl_validator_rs::batch::max_severity;
+use oparl_validator_rs::visit::Severity;
+
+#[test]
+fn test_failed_endpoint_is_an_error() {
+    assert_eq!(
+        max_severity(&[Ok(None), Ok(Some(Severity::Info))]),
+        Some(Severity::Info)
+    );
+    // The problems of the failed endpoint are unknown, so it must not pass `--fail-on error`
+    let results = [
+        Ok(Some(Severity::Warning)),
+        Err(anyhow::format_err!("The validator crashed")),
+    ];
+    assert_eq!(max_severity(&results), Some(Severity::Error));
+    assert_eq!(max_severity(&[]), None);
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use oparl_validator_rs::archive::ArchiveCache;
+use oparl_validator_rs::cache_dir::{invalidate_missing, parse_age, prune, verify};
+use oparl_validator_rs::client::{find_system, Cache, FileCache, ResponseMetadata, MANIFEST_FILE};
+use oparl_validator_rs::lang::Lang;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_file_cache_any_url() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let system = "https://oparl.example.org/api/system";
+    // Another host and a query string, which the prefix of the entrypoint didn't allow
+    let file = "https://files.example.com/download?id=12&format=pdf";
+    let metadata = ResponseMetadata {
+        content_type: Some("application/json".to_string()),
+        etag: Some("\"abc\"".to_string()),
+        last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+    };
+
+    let cache = FileCache::new(dir.clone())?;
+    assert_eq!(cache.get(system)?, None);
+    cache.set(system, b"{}")?;
+    cache.set_response(file, b"[1]", &metadata)?;
+    cache.set_inner("missing_ids.json", b"{}")?;
+
+    // Everything is read back from the manifest
+    let cache = FileCache::new(dir.clone())?;
+    assert_eq!(cache.get(system)?, Some(b"{}".to_vec()));
+    assert_eq!(cache.get(file)?, Some(b"[1]".to_vec()));
+    assert_eq!(cache.get_metadata(file)?, Some(metadata));
+    assert_eq!(
+        cache.get_metadata(system)?,
+        Some(ResponseMetadata::default())
+    );
+    let urls: Vec<_> = cache.entries().into_iter().map(|entry| entry.url).collect();
+    assert_eq!(urls, [file, system]);
+    assert!(dir.join("missing_ids.json").is_file());
+    // No temporary files are left behind
+    assert_eq!(fs_err::read_dir(dir.join("responses"))?.count(), 2);
+
+    // The last response for a url wins
+    cache.set(system, b"{\"id\": 1}")?;
+    let cache = FileCache::new(dir)?;
+    assert_eq!(cache.get(system)?, Some(b"{\"id\": 1}".to_vec()));
+    assert_eq!(cache.entries().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_file_cache_partial_manifest_line() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let url = "https://oparl.example.org/api/system";
+    FileCache::new(dir.clone())?.set(url, b"{}")?;
+    // As if we crashed while appending
+    fs_err::OpenOptions::new()
+        .append(true)
+        .open(dir.join(MANIFEST_FILE))?
+        .write_all(b"{\"url\": \"https://oparl.exa")?;
+
+    let cache = FileCache::new(dir)?;
+    assert_eq!(cache.get(url)?, Some(b"{}".to_vec()));
+    assert_eq!(cache.entries().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_file_cache_legacy_layout() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    fs_err::create_dir_all(dir.join("body"))?;
+    fs_err::write(dir.join("body/1.json"), b"{}")?;
+    let url = "https://oparl.example.org/api/body/1";
+
+    assert_eq!(FileCache::new(dir.clone())?.get(url)?, None);
+    let cache =
+        FileCache::new(dir)?.with_legacy_prefix("https://oparl.example.org/api/".to_string());
+    assert_eq!(cache.get(url)?, Some(b"{}".to_vec()));
+    // Urls with another prefix are simply not cached
+    assert_eq!(cache.get("https://other.example.org/body/1")?, None);
+    Ok(())
+}
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+/// A cache with a response from today and one from 2020
+fn cache_with_old_response(dir: &Path, new: &str, old: &str) -> Result<()> {
+    let cache = FileCache::new(dir.to_path_buf())?;
+    cache.set(new, b"{}")?;
+    cache.set(old, b"{}")?;
+    let manifest = fs_err::read_to_string(dir.join(MANIFEST_FILE))?;
+    let (new_line, old_line) = manifest.trim_end().split_once('\n').unwrap();
+    let fetched = &old_line[old_line.find("\"fetched\"").unwrap()..];
+    let fetched = &fetched[..fetched.find(',').unwrap()];
+    let old_line = old_line.replace(fetched, r#""fetched":"2020-01-01T00:00:00Z""#);
+    fs_err::write(
+        dir.join(MANIFEST_FILE),
+        format!("{}\n{}\n", new_line, old_line),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_file_cache_max_age() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let new = "https://oparl.example.org/api/body";
+    let old = "https://oparl.example.org/api/system";
+    cache_with_old_response(&dir, new, old)?;
+
+    let cache = FileCache::new(dir.clone())?;
+    assert_eq!(cache.get(old)?, Some(b"{}".to_vec()));
+    cache.set_inner("missing_ids.json", b"{}")?;
+    let cache = FileCache::new(dir.clone())?.with_max_age(Some(DAY));
+    assert_eq!(cache.get(new)?, Some(b"{}".to_vec()));
+    assert_eq!(cache.get(old)?, None);
+    assert_eq!(cache.get_inner("missing_ids.json")?, Some(b"{}".to_vec()));
+
+    // An outdated missing_ids.json is ignored as well
+    std::fs::File::options()
+        .write(true)
+        .open(dir.join("missing_ids.json"))?
+        .set_modified(SystemTime::now() - 2 * DAY)?;
+    assert_eq!(cache.get_inner("missing_ids.json")?, None);
+    Ok(())
+}
+
+#[test]
+fn test_parse_age() {
+    assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+    assert!(parse_age("12").is_err());
+    assert!(parse_age("12y").is_err());
+    // Too large for the seconds
+    assert!(parse_age("99999999999999999w").is_err());
+}
+
+#[test]
+fn test_cache_dir_prune_and_verify() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let new = "https://oparl.example.org/api/body";
+    let old = "https://oparl.example.org/api/system";
+    cache_with_old_response(&dir, new, old)?;
+    // Left over from a crash between writing the body and the manifest
+    fs_err::write(dir.join("responses/orphan.json"), b"{")?;
+
+    let invalid = verify(&dir, Lang::En)?;
+    assert_eq!(invalid.len(), 1);
+    assert!(invalid[0].0.ends_with("responses/orphan.json"));
+
+    assert_eq!(prune(&dir, DAY)?, 2);
+    assert_eq!(verify(&dir, Lang::En)?, vec![]);
+    let cache = FileCache::new(dir.clone())?;
+    assert_eq!(cache.entries().len(), 1);
+    assert_eq!(cache.get(new)?, Some(b"{}".to_vec()));
+    assert_eq!(fs_err::read_dir(dir.join("responses"))?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_cache_dir_invalidate_missing() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let missing_ids = r#"{"https://oparl.example.org/api/paper/1": false, "https://oparl.example.org/api/paper/2": false, "https://oparl.example.org/api/person/1": false}"#;
+    fs_err::write(dir.join("missing_ids.json"), missing_ids)?;
+
+    assert_eq!(invalidate_missing(&dir, &["/paper/".to_string()])?, 2);
+    assert_eq!(
+        fs_err::read_to_string(dir.join("missing_ids.json"))?,
+        r#"{"https://oparl.example.org/api/person/1":false}"#
+    );
+    assert_eq!(invalidate_missing(&dir, &[])?, 1);
+    assert!(!dir.join("missing_ids.json").exists());
+    Ok(())
+}
+
+#[test]
+fn test_archive_cache_write() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let archive = dir.join("cache.tar.zst");
+    let url = "https://oparl.example.org/api/body?page=2";
+    let metadata = ResponseMetadata {
+        etag: Some("\"1\"".to_string()),
+        ..ResponseMetadata::default()
+    };
+
+    let cache = ArchiveCache::open(&archive)?;
+    assert_eq!(cache.get(url)?, None);
+    cache.set_response(url, b"{}", &metadata)?;
+    cache.set_inner("missing_ids.json", b"{}")?;
+    cache.write()?;
+
+    let cache = ArchiveCache::open(&archive)?;
+    assert_eq!(cache.get(url)?, Some(b"{}".to_vec()));
+    assert_eq!(cache.get_metadata(url)?, Some(metadata));
+    assert_eq!(cache.get_inner("missing_ids.json")?, Some(b"{}".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn test_archive_cache_pack() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().to_path_buf();
+    let cache_dir = dir.join("cache");
+    let system = "https://oparl.example.org/api/";
+    let cache = FileCache::new(cache_dir.clone())?;
+    cache.set(
+        system,
+        br#"{"id": "https://oparl.example.org/api/", "type": "https://schema.oparl.org/1.1/System"}"#,
+    )?;
+    cache.set("https://oparl.example.org/api/body", b"[]")?;
+    cache.set_inner("missing_ids.json", b"{}")?;
+    // The state of a run and unfinished writes aren't packed
+    for name in [
+        "checkpoint.json",
+        "incremental.json",
+        "responses/abc.json.0.tmp",
+    ] {
+        fs_err::write(cache_dir.join(name), b"{}")?;
+    }
+
+    let archive = dir.join("cache.tar.zst");
+    assert_eq!(ArchiveCache::pack(&cache_dir, &archive)?, 4);
+    let cache = ArchiveCache::open(&archive)?;
+    assert_eq!(
+        cache.get("https://oparl.example.org/api/body")?,
+        Some(b"[]".to_vec())
+    );
+    assert_eq!(cache.get_inner("missing_ids.json")?, Some(b"{}".to_vec()));
+    assert_eq!(cache.get_inner("checkpoint.json")?, None);
+    // The system url doesn't end with `system`, so it's found by its type
+    let urls = cache.entries().into_iter().map(|entry| entry.url).collect();
+    assert_eq!(find_system(&cache, urls)?.as_deref(), Some(system));
+    Ok(())
+}
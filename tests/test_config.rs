@@ -0,0 +1,158 @@
+use anyhow::Result;
+use oparl_validator_rs::config::Config;
+use oparl_validator_rs::lang::Lang;
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::{ProblemKind, Severity};
+
+mod common;
+use common::problem;
+
+#[test]
+fn test_severity_overrides() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.yml");
+    fs_err::write(
+        &path,
+        "severity:\n  UsingHttp: error\n  VendorField: warning\n",
+    )?;
+    let mut config = Config::load(&path)?;
+    config.add_severity_override("VendorField=info")?;
+    assert!(config.add_severity_override("UsingHtp=info").is_err());
+    assert!(config.add_severity_override("UsingHttp=fatal").is_err());
+
+    assert_eq!(config.severity(&ProblemKind::UsingHttp), Severity::Error);
+    // The command line wins over the config file
+    assert_eq!(config.severity(&ProblemKind::VendorField), Severity::Info);
+    assert_eq!(
+        config.severity(&ProblemKind::EmptyString),
+        Severity::Warning
+    );
+
+    let storage = Storage::with_config(config);
+    assert_eq!(storage.max_severity(), None);
+    storage.add_problem(problem(ProblemKind::VendorField, "", None));
+    storage.add_problem(problem(ProblemKind::EmptyString, "", None));
+    assert_eq!(storage.max_severity(), Some(Severity::Warning));
+    storage.add_problem(problem(ProblemKind::UsingHttp, "", None));
+    assert_eq!(
+        storage.severity_counts(),
+        [
+            (Severity::Error, 1),
+            (Severity::Warning, 1),
+            (Severity::Info, 1)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unknown_kind_in_config() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.yml");
+    fs_err::write(&path, "severity:\n  UsingHtp: error\n")?;
+    assert!(Config::load(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_lang_in_config() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.yml");
+    fs_err::write(&path, "lang: en\nignore:\n  - OPARL007\n")?;
+    let config = Config::load(&path)?;
+    assert_eq!(config.lang, Lang::En);
+    assert!(!config.is_enabled("UsingHttp"));
+    assert_eq!(
+        ProblemKind::EmptyString.message("Paper.name", config.lang),
+        "The field Paper.name is set, but empty"
+    );
+    assert_eq!(Config::default().lang, Lang::De);
+    Ok(())
+}
+
+#[test]
+fn test_select_and_ignore() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.yml");
+    fs_err::write(
+        &path,
+        "select:\n  - OPARL007\n  - EmptyString\n  - oparl005\n",
+    )?;
+    let mut config = Config::load(&path)?;
+    config.add_ignore("OPARL006")?;
+    assert!(config.add_select("OPARL999").is_err());
+
+    assert!(config.is_enabled("UsingHttp"));
+    assert!(config.is_enabled("RequiredFieldMissing"));
+    // Ignore wins over select
+    assert!(!config.is_enabled("EmptyString"));
+    assert!(!config.is_enabled("InvalidJson"));
+    assert!(config.any_enabled(&["InvalidJson", "UsingHttp"]));
+
+    let storage = Storage::with_config(config);
+    storage.add_problem(problem(ProblemKind::UsingHttp, "", None));
+    storage.add_problem(problem(ProblemKind::EmptyString, "", None));
+    storage.add_problem(problem(ProblemKind::InvalidJson, "", None));
+    assert_eq!(storage.problems.lock().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_rule_codes() {
+    assert_eq!(ProblemKind::UsingHttp.code(), "OPARL007");
+    assert_eq!(ProblemKind::ObjectMissing("Paper").code(), "OPARL003");
+    let mut codes: Vec<&str> = ProblemKind::RULES.iter().map(|(code, _)| *code).collect();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), ProblemKind::RULES.len());
+}
+
+#[test]
+fn test_rules_match_codes() {
+    // One kind per variant, in the order of the codes
+    let kinds = [
+        ProblemKind::HttpRequestFailedTooOften,
+        ProblemKind::HttpRequestFailedRetrying,
+        ProblemKind::ObjectMissing("Paper"),
+        ProblemKind::ObjectNotInList("Paper"),
+        ProblemKind::RequiredFieldMissing,
+        ProblemKind::EmptyString,
+        ProblemKind::UsingHttp,
+        ProblemKind::InvalidJson,
+        ProblemKind::WrongType,
+        ProblemKind::InvalidDate,
+        ProblemKind::DateTimeMissingTimezone,
+        ProblemKind::DateInsteadOfDateTime,
+        ProblemKind::WrongObjectType("Paper"),
+        ProblemKind::MissingBacklink("Paper"),
+        ProblemKind::PaginationTotalElements,
+        ProblemKind::PaginationTotalPages,
+        ProblemKind::PaginationCurrentPage,
+        ProblemKind::PaginationElementsPerPage,
+        ProblemKind::ListLinkMissing("Paper"),
+        ProblemKind::ListLinkWrong("Paper"),
+        ProblemKind::PaginationLoop,
+        ProblemKind::DuplicateObjectInList("Paper"),
+        ProblemKind::FilterRequestFailed("Paper"),
+        ProblemKind::FilterWrongObjects("Paper"),
+        ProblemKind::FilterMissingObjects("Paper"),
+        ProblemKind::FilterLimitIgnored,
+        ProblemKind::BrokenAccessUrl,
+        ProblemKind::BrokenDownloadUrl,
+        ProblemKind::DeadWebPage,
+        ProblemKind::UnreachableLicense,
+        ProblemKind::ListObjectDiffers("Paper"),
+        ProblemKind::DeletedObjectHasPayload,
+        ProblemKind::DeletedObjectWithoutModified,
+        ProblemKind::LinkToDeletedObject("Paper"),
+        ProblemKind::VendorField,
+        ProblemKind::MisspelledField("Paper"),
+        ProblemKind::UnknownField,
+        ProblemKind::NoCacheValidators,
+        ProblemKind::ConditionalRequestIgnored,
+    ];
+    assert_eq!(
+        kinds.map(|kind| (kind.code(), kind.name())),
+        ProblemKind::RULES
+    );
+}
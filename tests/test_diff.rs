@@ -0,0 +1,203 @@
+use anyhow::Result;
+use oparl_validator_rs::cli::{write_machine_report, ReportFormat};
+use oparl_validator_rs::client::{Cache, FileCache};
+use oparl_validator_rs::config::Config;
+use oparl_validator_rs::diff::{RunDiff, RunSummary};
+use oparl_validator_rs::lang::Lang;
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::{Problem, ProblemKind};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+mod common;
+use common::problem;
+
+const PAPER_1: &str = "https://example.org/paper/1";
+const PAPER_2: &str = "https://example.org/paper/2";
+
+fn write_report(
+    dir: &Path,
+    name: &str,
+    problems: Vec<Problem>,
+    format: ReportFormat,
+) -> Result<PathBuf> {
+    let storage = Storage::new();
+    for problem in problems {
+        storage.add_problem(problem);
+    }
+    let mut report = Vec::new();
+    write_machine_report(&storage, format, &mut report)?;
+    let path = dir.join(name);
+    fs_err::write(&path, report)?;
+    Ok(path)
+}
+
+#[tokio::test]
+async fn test_diff_reports() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let old = write_report(
+        dir.path(),
+        "old.json",
+        vec![
+            problem(
+                ProblemKind::RequiredFieldMissing,
+                "Paper.type",
+                Some(PAPER_1),
+            ),
+            problem(
+                ProblemKind::RequiredFieldMissing,
+                "Paper.type",
+                Some(PAPER_2),
+            ),
+            problem(ProblemKind::EmptyString, "Paper.name", Some(PAPER_1)),
+            problem(ProblemKind::UsingHttp, "", Some(PAPER_1)),
+        ],
+        ReportFormat::Json,
+    )?;
+    let new = write_report(
+        dir.path(),
+        "new.ndjson",
+        vec![
+            problem(
+                ProblemKind::RequiredFieldMissing,
+                "Paper.type",
+                Some(PAPER_2),
+            ),
+            problem(ProblemKind::UsingHttp, "", Some(PAPER_1)),
+            problem(ProblemKind::ObjectMissing("Meeting"), "", Some(PAPER_1)),
+        ],
+        ReportFormat::Ndjson,
+    )?;
+
+    let diff = RunDiff::new(
+        &RunSummary::load(&old, &Config::default()).await?,
+        &RunSummary::load(&new, &Config::default()).await?,
+    );
+    let mut out = Vec::new();
+    diff.write(&mut out, Lang::De)?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        "=== Vergleich: 1 behoben, 1 neu, 1 mit geänderter Anzahl ===
+== Behoben ==
+[OPARL006] Das Feld Paper.name ist angegeben, hat aber keinen Inhalt (vorher 1 Fälle)
+== Neu ==
+[OPARL003] Das Objekt vom Typ Meeting wurden von einem anderen Objekt verlinkt, ist aber nicht abrufbar (1 Fälle)
+== Geänderte Anzahl ==
+[OPARL005] Das zwingend vorgeschriebene Feld Paper.type fehlt (1 statt vorher 2 Fälle)
+"
+    );
+    Ok(())
+}
+
+/// Writes a minimal api into a cache directory, with or without a type for the paper, either
+/// through the manifest or in the layout of older versions
+fn write_cache_dir(dir: PathBuf, paper_type: bool, legacy: bool) -> Result<PathBuf> {
+    fs_err::create_dir_all(&dir)?;
+    let list = |data: serde_json::Value| json!({"data": data, "pagination": {}, "links": {}});
+    let mut paper = json!({"id": "https://example.org/paper/1"});
+    if paper_type {
+        paper["type"] = json!("https://schema.oparl.org/1.1/Paper");
+    }
+    let files = [
+        (
+            "system",
+            json!({
+                "id": "https://example.org/system",
+                "type": "https://schema.oparl.org/1.1/System",
+                "oparlVersion": "https://schema.oparl.org/1.1/",
+                "body": "https://example.org/body"
+            }),
+        ),
+        (
+            "body",
+            list(json!([{
+                "id": "https://example.org/body/1",
+                "type": "https://schema.oparl.org/1.1/Body",
+                "system": "https://example.org/system",
+                "name": "Example",
+                "organization": "https://example.org/organization",
+                "person": "https://example.org/person",
+                "meeting": "https://example.org/meeting",
+                "paper": "https://example.org/paper",
+                "legislativeTerm": []
+            }])),
+        ),
+        ("organization", list(json!([]))),
+        ("person", list(json!([]))),
+        ("meeting", list(json!([]))),
+        ("paper", list(json!([paper]))),
+    ];
+    let cache = FileCache::new(dir.clone())?;
+    for (name, value) in files {
+        if legacy {
+            fs_err::write(
+                dir.join(name).with_extension("json"),
+                serde_json::to_vec(&value)?,
+            )?;
+        } else {
+            cache.set(
+                &format!("https://example.org/{}", name),
+                &serde_json::to_vec(&value)?,
+            )?;
+        }
+    }
+    Ok(dir)
+}
+
+#[tokio::test]
+async fn test_diff_cache_dirs() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let old = write_cache_dir(dir.path().join("old"), false, true)?;
+    let new = write_cache_dir(dir.path().join("new"), true, false)?;
+    let diff = RunDiff::new(
+        &RunSummary::load(&old, &Config::default()).await?,
+        &RunSummary::load(&new, &Config::default()).await?,
+    );
+    assert_eq!(
+        diff.fixed,
+        [(
+            "[OPARL005] Das zwingend vorgeschriebene Feld Paper.type fehlt".to_string(),
+            1
+        )]
+    );
+    assert!(diff.new.is_empty());
+    assert!(diff.changed.is_empty());
+    // Validating again doesn't write to the cache
+    assert!(!old.join("manifest.ndjson").exists());
+    assert!(!new.join("missing_ids.json").exists());
+
+    let mut config = Config::default();
+    config.add_ignore("OPARL005")?;
+    let diff = RunDiff::new(
+        &RunSummary::load(&old, &config).await?,
+        &RunSummary::load(&new, &config).await?,
+    );
+    assert_eq!(diff, RunDiff::default());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_diff_cache_dir_with_missing_response() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let complete = write_cache_dir(dir.path().join("complete"), true, true)?;
+    let incomplete = write_cache_dir(dir.path().join("incomplete"), true, true)?;
+    fs_err::remove_file(incomplete.join("person.json"))?;
+
+    // A response missing from the cache fails right away instead of being retried
+    let started = Instant::now();
+    let diff = RunDiff::new(
+        &RunSummary::load(&complete, &Config::default()).await?,
+        &RunSummary::load(&incomplete, &Config::default()).await?,
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let codes: Vec<_> = diff.new.iter().map(|(message, _)| &message[..10]).collect();
+    assert_eq!(
+        codes,
+        [format!(
+            "[{}]",
+            ProblemKind::HttpRequestFailedTooOften.code()
+        )]
+    );
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use oparl_validator_rs::explain::{explanation, write_explanation};
+use oparl_validator_rs::lang::Lang;
+use oparl_validator_rs::visit::ProblemKind;
+use serde_json::Value;
+
+#[test]
+fn test_every_rule_has_an_explanation() {
+    for (code, name) in ProblemKind::RULES {
+        let explanation = explanation(name, Lang::En)
+            .unwrap_or_else(|| panic!("No explanation for {} {}", code, name));
+        serde_json::from_str::<Value>(explanation.compliant)
+            .unwrap_or_else(|err| panic!("Invalid compliant example for {}: {}", name, err));
+        // The example for invalid json is invalid on purpose
+        if name != "InvalidJson" {
+            serde_json::from_str::<Value>(explanation.non_compliant).unwrap_or_else(|err| {
+                panic!("Invalid non-compliant example for {}: {}", name, err)
+            });
+        }
+    }
+}
+
+#[test]
+fn test_explain_by_code_and_name() -> Result<()> {
+    let mut by_code = Vec::new();
+    write_explanation("oparl007", Lang::De, &mut by_code)?;
+    let mut by_name = Vec::new();
+    write_explanation("UsingHttp", Lang::De, &mut by_name)?;
+    assert_eq!(by_code, by_name);
+    assert!(String::from_utf8(by_code)?.starts_with("OPARL007 UsingHttp\n"));
+    assert!(write_explanation("OPARL999", Lang::De, &mut Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_explain_in_english() -> Result<()> {
+    let mut output = Vec::new();
+    write_explanation("OPARL007", Lang::En, &mut output)?;
+    let output = String::from_utf8(output)?;
+    assert!(output.contains("\nThe ids use http instead of https."));
+    assert!(output.contains("\nSpec: Architecture: URLs\n"));
+    assert!(output.contains("\nCorrect:\n"));
+    assert!(output.contains("\nIncorrect:\n"));
+    assert!(output.contains("\nFix: "));
+    assert!(!output.contains("Richtig"));
+    Ok(())
+}
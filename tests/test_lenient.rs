@@ -0,0 +1,106 @@
+use oparl_validator_rs::external_list::ExternalList;
+use oparl_validator_rs::lang::Lang;
+use oparl_validator_rs::lenient::{deserialize_lenient, deserialize_list_lenient};
+use oparl_validator_rs::schema::{Meeting, Paper};
+use oparl_validator_rs::visit::ProblemKind;
+use serde_json::json;
+
+#[test]
+fn test_wrong_types_are_dropped() {
+    let page = json!({
+        "data": [
+            {
+                "id": "https://example.org/paper/1",
+                "type": "https://schema.oparl.org/1.1/Paper",
+                "mainFile": {"id": "https://example.org/file/1", "size": "12"},
+                "consultation": [{"id": "https://example.org/consultation/1", "role": 5}]
+            },
+            {
+                "id": "https://example.org/paper/2",
+                "type": "https://schema.oparl.org/1.1/Paper",
+                "name": "Unaffected"
+            }
+        ],
+        "pagination": {},
+        "links": {}
+    });
+    let (page, problems) = deserialize_lenient::<ExternalList<Paper>>(
+        page,
+        "https://example.org/paper",
+        "ExternalList",
+        "Paper",
+        Lang::De,
+    )
+    .unwrap();
+
+    assert_eq!(page.data.len(), 2);
+    let main_file = page.data[0].main_file.as_ref().unwrap();
+    assert_eq!(main_file.size, None);
+    assert_eq!(
+        main_file.id.as_ref().unwrap().as_str(),
+        "https://example.org/file/1"
+    );
+    assert_eq!(page.data[1].name.as_deref(), Some("Unaffected"));
+
+    let mut paths: Vec<_> = problems
+        .iter()
+        .map(|problem| {
+            assert_eq!(problem.kind, ProblemKind::WrongType);
+            assert_eq!(problem.id.as_deref(), Some("https://example.org/paper/1"));
+            problem.path.as_str()
+        })
+        .collect();
+    paths.sort();
+    assert_eq!(paths, ["Paper.consultation.role", "Paper.mainFile.size"]);
+}
+
+#[test]
+fn test_broken_list_is_rejected() {
+    let page = json!({"data": [{"cancelled": "0"}], "links": {}});
+    let (_, error) = deserialize_lenient::<ExternalList<Meeting>>(
+        page,
+        "https://example.org/meeting",
+        "ExternalList",
+        "Meeting",
+        Lang::De,
+    )
+    .unwrap_err();
+    assert_eq!(error, "missing field `pagination`");
+}
+
+#[tokio::test]
+async fn test_list_items_are_fixed_one_by_one() {
+    let file = |id: usize| json!({"id": format!("https://example.org/file/{}", id), "size": "12"});
+    let page = json!({
+        "data": [
+            {"id": "https://example.org/paper/1", "mainFile": file(1), "auxiliaryFile": [file(2)]},
+            {"id": "https://example.org/paper/2", "mainFile": file(3)},
+            // Not an object at all, only the whole page can drop it
+            5
+        ],
+        "pagination": {"totalPages": "1"},
+        "links": {}
+    });
+    let (page, problems) =
+        deserialize_list_lenient::<Paper>(page, "https://example.org/paper", "Paper", Lang::En)
+            .await
+            .unwrap();
+
+    assert_eq!(page.data.len(), 2);
+    assert_eq!(page.pagination.total_pages, None);
+    let mut paths: Vec<_> = problems
+        .iter()
+        .map(|problem| problem.path.as_str())
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            "ExternalList.pagination.totalPages",
+            "Paper",
+            "Paper.auxiliaryFile.size",
+            "Paper.mainFile.size",
+            "Paper.mainFile.size"
+        ]
+    );
+}
@@ -0,0 +1,85 @@
+use oparl_validator_rs::cli::ReporterCli;
+use oparl_validator_rs::links::check_links;
+use oparl_validator_rs::reporter::Reporter;
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::ProblemKind;
+
+mod common;
+use common::Response;
+
+/// Answers `/missing*` with 404, HEAD on `/no-head*` with 405 and everything else with 200.
+/// Requests without a user agent get a 403, and a GET after a 404 for HEAD a 500.
+fn serve() -> String {
+    common::serve(|request| {
+        Response::status(if !request.headers.contains_key("user-agent") {
+            "403 Forbidden"
+        } else if request.line.starts_with("GET /missing") {
+            "500 Internal Server Error"
+        } else if request.line.contains(" /missing") {
+            "404 Not Found"
+        } else if request.line.starts_with("HEAD /no-head") {
+            "405 Method Not Allowed"
+        } else {
+            "200 OK"
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_check_links() {
+    let address = serve();
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    for (path, url, source) in [
+        ("File.accessUrl", "/missing.pdf", "file/1"),
+        ("File.accessUrl", "/missing.pdf", "file/2"),
+        ("File.downloadUrl", "/no-head.pdf", "file/1"),
+        ("Paper.web", "/paper/1.html", "paper/1"),
+        ("Body.license", "/missing-license", "body/1"),
+        // Lists are loaded anyway, so we don't check them here
+        ("Body.paper", "/missing-list", "body/1"),
+    ] {
+        reporter.add_other_url(
+            path,
+            format!("{}{}", address, url),
+            format!("https://example.org/{}", source),
+        );
+    }
+
+    check_links(&reporter).await.unwrap();
+
+    let mut problems: Vec<_> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .map(|problem| {
+            (
+                problem.kind.clone(),
+                problem.path.clone(),
+                problem.detail.clone(),
+            )
+        })
+        .collect();
+    problems.sort();
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::BrokenAccessUrl,
+                "File.accessUrl".to_string(),
+                format!(
+                    "{}/missing.pdf (Status 404 Not Found) wird verlinkt von 2 Objekten, z.B. https://example.org/file/1",
+                    address
+                )
+            ),
+            (
+                ProblemKind::UnreachableLicense,
+                "Body.license".to_string(),
+                format!(
+                    "{}/missing-license (Status 404 Not Found) wird verlinkt von https://example.org/body/1",
+                    address
+                )
+            ),
+        ]
+    );
+}
@@ -0,0 +1,579 @@
+use anyhow::Result;
+use oparl_validator_rs::cli::ReporterCli;
+use oparl_validator_rs::client::{Cache, OparlClient};
+use oparl_validator_rs::config::Config;
+use oparl_validator_rs::filters::check_filters;
+use oparl_validator_rs::reporter::Reporter;
+use oparl_validator_rs::storage::{ListObject, Storage};
+use oparl_validator_rs::visit::ProblemKind;
+use oparl_validator_rs::{validate_oparl_api, ValidationOptions};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Serves a small handwritten api from memory
+struct MemoryCache {
+    files: HashMap<String, Vec<u8>>,
+    /// The statuses of the objects that weren't in the lists
+    missing_ids: Mutex<Option<Vec<u8>>>,
+    /// Urls whose first request fails as if the connection was reset
+    flaky: Mutex<HashSet<String>>,
+}
+
+impl MemoryCache {
+    fn new(files: Vec<(&str, Value)>) -> Self {
+        let files = files
+            .into_iter()
+            .map(|(url, value)| (url.to_string(), serde_json::to_vec(&value).unwrap()))
+            .collect();
+        Self {
+            files,
+            missing_ids: Mutex::new(None),
+            flaky: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn insert(&mut self, url: &str, value: Value) {
+        self.insert_raw(url, &serde_json::to_vec(&value).unwrap());
+    }
+
+    fn insert_raw(&mut self, url: &str, data: &[u8]) {
+        self.files.insert(url.to_string(), data.to_vec());
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        if self.flaky.lock().remove(key) {
+            anyhow::bail!("Connection reset by peer");
+        }
+        match self.files.get(key) {
+            Some(data) => Ok(Some(data.clone())),
+            None if key == "missing_ids.json" => Ok(self.missing_ids.lock().clone()),
+            None => panic!("No hit for cache key: {}", key),
+        }
+    }
+
+    fn set_inner(&self, key: &str, data: &[u8]) -> Result<()> {
+        assert_eq!(key, "missing_ids.json", "Must not write in tests");
+        *self.missing_ids.lock() = Some(data.to_vec());
+        Ok(())
+    }
+}
+
+fn list(data: Vec<Value>, next: Option<&str>) -> Value {
+    json!({"data": data, "pagination": {}, "links": {"next": next}})
+}
+
+fn paper(id: usize) -> Value {
+    json!({
+        "id": format!("https://example.org/paper/{}", id),
+        "type": "https://schema.oparl.org/1.1/Paper"
+    })
+}
+
+/// System and body with empty lists, only the papers are missing
+fn base_api() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "https://example.org/system",
+            json!({
+                "id": "https://example.org/system",
+                "type": "https://schema.oparl.org/1.1/System",
+                "oparlVersion": "https://schema.oparl.org/1.1/",
+                "body": "https://example.org/body"
+            }),
+        ),
+        (
+            "https://example.org/body",
+            list(
+                vec![json!({
+                    "id": "https://example.org/body/1",
+                    "type": "https://schema.oparl.org/1.1/Body",
+                    "system": "https://example.org/system",
+                    "name": "Example",
+                    "organization": "https://example.org/organization",
+                    "person": "https://example.org/person",
+                    "meeting": "https://example.org/meeting",
+                    "paper": "https://example.org/paper",
+                    "legislativeTerm": []
+                })],
+                None,
+            ),
+        ),
+        ("https://example.org/organization", list(vec![], None)),
+        ("https://example.org/person", list(vec![], None)),
+        ("https://example.org/meeting", list(vec![], None)),
+    ]
+}
+
+/// An api with a paper list whose second page links back to the first one
+fn looping_api() -> MemoryCache {
+    let mut files = base_api();
+    files.extend([
+        (
+            "https://example.org/paper",
+            list(
+                vec![paper(1), paper(2)],
+                Some("https://example.org/paper?page=2"),
+            ),
+        ),
+        (
+            "https://example.org/paper?page=2",
+            list(vec![paper(2), paper(3)], Some("https://example.org/paper")),
+        ),
+    ]);
+    MemoryCache::new(files)
+}
+
+#[tokio::test]
+async fn test_pagination_loop_and_duplicates() -> Result<()> {
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(looping_api()),
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let problems = reporter.get_storage().problems.lock().clone();
+    let find = |kind: ProblemKind| {
+        problems
+            .iter()
+            .find(|problem| problem.kind == kind)
+            .unwrap_or_else(|| panic!("No {:?} in {:#?}", kind, problems))
+    };
+    assert_eq!(
+        find(ProblemKind::PaginationLoop).url.as_deref(),
+        Some("https://example.org/paper")
+    );
+    assert_eq!(
+        find(ProblemKind::DuplicateObjectInList("Paper"))
+            .id
+            .as_deref(),
+        Some("https://example.org/paper/2")
+    );
+    assert!(reporter
+        .get_storage()
+        .known_ids
+        .lock()
+        .contains("https://example.org/paper/3"));
+    Ok(())
+}
+
+fn dated_paper(id: usize, day: usize, deleted: bool) -> Value {
+    let mut paper = paper(id);
+    let date = format!("2023-01-0{}T00:00:00Z", day);
+    paper["created"] = json!(date);
+    paper["modified"] = json!(date);
+    paper["deleted"] = json!(deleted);
+    paper
+}
+
+/// An api that ignores `created_until` and `limit` and forgets the deleted paper
+/// with `modified_since`
+fn filter_api() -> MemoryCache {
+    let papers = vec![
+        dated_paper(1, 1, false),
+        dated_paper(2, 2, false),
+        dated_paper(3, 3, true),
+    ];
+    let mut files = base_api();
+    files.push(("https://example.org/paper", list(papers.clone(), None)));
+    let mut cache = MemoryCache::new(files);
+    for (filter, papers) in [
+        ("created_since", &papers[1..]),
+        ("created_until", &papers[..]),
+        ("modified_since", &papers[1..2]),
+        ("modified_until", &papers[..2]),
+    ] {
+        cache.insert(
+            &format!(
+                "https://example.org/paper?{}=2023-01-02T00%3A00%3A00Z",
+                filter
+            ),
+            list(papers.to_vec(), None),
+        );
+    }
+    cache.insert("https://example.org/paper?limit=2", list(papers, None));
+    cache
+}
+
+#[tokio::test]
+async fn test_filters_invalid_list_url() -> Result<()> {
+    let storage = Storage::new();
+    for id in 1..=3 {
+        storage.add_list_object(
+            "not a url",
+            ListObject {
+                id: format!("https://example.org/paper/{}", id),
+                created: Some("2023-01-01T00:00:00Z".to_string()),
+                modified: None,
+                deleted: false,
+            },
+        );
+    }
+    let reporter = ReporterCli::new(storage, true, None);
+    check_filters(
+        "Paper",
+        "not a url",
+        &reporter,
+        &OparlClient::new(filter_api()),
+    )
+    .await?;
+
+    let kinds: Vec<ProblemKind> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .map(|problem| problem.kind.clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ProblemKind::FilterRequestFailed("created_since"),
+            ProblemKind::FilterRequestFailed("created_until"),
+            ProblemKind::FilterRequestFailed("limit"),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filters() -> Result<()> {
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    let options = ValidationOptions {
+        check_filters: true,
+        ..Default::default()
+    };
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(filter_api()),
+        &options,
+    )
+    .await?;
+
+    let mut problems: Vec<(ProblemKind, String)> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .filter(|problem| problem.kind.name().starts_with("Filter"))
+        .map(|problem| (problem.kind.clone(), problem.detail.clone()))
+        .collect();
+    problems.sort();
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::FilterWrongObjects("created_until"),
+                "https://example.org/paper?created_until=2023-01-02T00%3A00%3A00Z: 1 Objekte passen nicht zum Filter, z.B. https://example.org/paper/3 mit created 2023-01-03T00:00:00Z".to_string()
+            ),
+            (
+                ProblemKind::FilterMissingObjects("modified_since"),
+                "https://example.org/paper?modified_since=2023-01-02T00%3A00%3A00Z: 1 passende Objekte fehlen, z.B. https://example.org/paper/3, darunter 1 gelöschte".to_string()
+            ),
+            (
+                ProblemKind::FilterLimitIgnored,
+                "https://example.org/paper?limit=2: 3 statt höchstens 2 Objekte auf der ersten Seite".to_string()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+/// An api with a paper linking a person that is only available individually, which in turn
+/// links a location, and an organization that returns an html error page
+fn linked_api() -> MemoryCache {
+    let mut paper = paper(1);
+    paper["originatorPerson"] = json!(["https://example.org/person/1"]);
+    paper["underDirectionOf"] = json!(["https://example.org/organization/1"]);
+    let mut files = base_api();
+    files.push(("https://example.org/paper", list(vec![paper], None)));
+    let mut cache = MemoryCache::new(files);
+    cache.insert(
+        "https://example.org/person/1",
+        json!({
+            "id": "https://example.org/person/1",
+            "type": "https://schema.oparl.org/1.1/Person",
+            "location": "https://example.org/location/1"
+        }),
+    );
+    cache.insert(
+        "https://example.org/location/1",
+        json!({
+            "id": "https://example.org/location/1",
+            "type": "https://schema.oparl.org/1.1/Location",
+            "description": 42
+        }),
+    );
+    cache.insert_raw(
+        "https://example.org/organization/1",
+        b"<html>Internal Server Error</html>",
+    );
+    cache
+}
+
+#[tokio::test]
+async fn test_linked_objects() -> Result<()> {
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(linked_api()),
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let mut problems: Vec<(ProblemKind, String)> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .map(|problem| {
+            (
+                problem.kind.clone(),
+                problem.url.clone().unwrap_or_default(),
+            )
+        })
+        // Leave out the problems with the handwritten lists
+        .filter(|(kind, _)| {
+            !matches!(
+                kind,
+                ProblemKind::RequiredFieldMissing | ProblemKind::ListLinkMissing(_)
+            )
+        })
+        .collect();
+    problems.sort();
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::ObjectMissing("Organization"),
+                "https://example.org/organization/1".to_string()
+            ),
+            (
+                ProblemKind::ObjectNotInList("Location"),
+                "https://example.org/location/1".to_string()
+            ),
+            (
+                ProblemKind::ObjectNotInList("Person"),
+                "https://example.org/person/1".to_string()
+            ),
+            (
+                ProblemKind::WrongType,
+                "https://example.org/location/1".to_string()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_linked_objects_retry() -> Result<()> {
+    let cache = linked_api();
+    cache
+        .flaky
+        .lock()
+        .insert("https://example.org/person/1".to_string());
+    let client = OparlClient::new(cache);
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &client,
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let problems = reporter.get_storage().problems.lock();
+    let person = |kind: &ProblemKind| {
+        problems.iter().any(|problem| {
+            problem.kind == *kind && problem.url.as_deref() == Some("https://example.org/person/1")
+        })
+    };
+    assert!(person(&ProblemKind::HttpRequestFailedRetrying));
+    assert!(person(&ProblemKind::ObjectNotInList("Person")));
+    // Only the definite answer is remembered, not the connection reset
+    let missing_ids: HashMap<String, bool> =
+        serde_json::from_slice(&client.cache.missing_ids.lock().clone().unwrap())?;
+    assert_eq!(
+        missing_ids,
+        HashMap::from([("https://example.org/organization/1".to_string(), false)])
+    );
+    Ok(())
+}
+
+/// Two papers where the first one has a different name and no consultation when fetched by id
+fn differing_api() -> MemoryCache {
+    let mut list_paper = paper(1);
+    list_paper["name"] = json!("Haushalt 2023");
+    list_paper["consultation"] = json!([{
+        "id": "https://example.org/consultation/1",
+        "type": "https://schema.oparl.org/1.1/Consultation",
+        "role": "Beratung"
+    }]);
+    let mut fetched_paper = paper(1);
+    fetched_paper["name"] = json!("Haushalt 2024");
+    let mut files = base_api();
+    files.push((
+        "https://example.org/paper",
+        list(vec![list_paper, paper(2)], None),
+    ));
+    files.push(("https://example.org/paper/1", fetched_paper));
+    files.push(("https://example.org/paper/2", paper(2)));
+    MemoryCache::new(files)
+}
+
+#[tokio::test]
+async fn test_compare_sample() -> Result<()> {
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    let options = ValidationOptions {
+        compare_sample: 5,
+        ..Default::default()
+    };
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(differing_api()),
+        &options,
+    )
+    .await?;
+
+    let mut problems: Vec<(String, String)> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .filter(|problem| problem.kind == ProblemKind::ListObjectDiffers("Paper"))
+        .map(|problem| (problem.path.clone(), problem.detail.clone()))
+        .collect();
+    problems.sort();
+    assert_eq!(
+        problems,
+        [
+            (
+                "Paper.consultation".to_string(),
+                "https://example.org/paper/1: Paper.consultation".to_string()
+            ),
+            (
+                "Paper.name".to_string(),
+                "https://example.org/paper/1: Paper.name".to_string()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+/// A live paper linking to a deleted paper that still has its name, and a deleted paper
+/// without `modified`
+fn deleted_api() -> MemoryCache {
+    let mut live_paper = dated_paper(1, 1, false);
+    live_paper["relatedPaper"] = json!(["https://example.org/paper/2"]);
+    let mut deleted_paper = dated_paper(2, 2, true);
+    deleted_paper["name"] = json!("Haushalt 2023");
+    let mut unmodified_paper = dated_paper(3, 3, true);
+    unmodified_paper["modified"] = Value::Null;
+    let mut files = base_api();
+    files.push((
+        "https://example.org/paper",
+        list(vec![live_paper, deleted_paper, unmodified_paper], None),
+    ));
+    MemoryCache::new(files)
+}
+
+#[tokio::test]
+async fn test_deleted_objects() -> Result<()> {
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(deleted_api()),
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let mut problems: Vec<(ProblemKind, String, Option<String>)> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .filter(|problem| {
+            matches!(
+                problem.kind,
+                ProblemKind::DeletedObjectHasPayload
+                    | ProblemKind::DeletedObjectWithoutModified
+                    | ProblemKind::LinkToDeletedObject(_)
+            )
+        })
+        .map(|problem| {
+            (
+                problem.kind.clone(),
+                problem.path.clone(),
+                problem.id.clone(),
+            )
+        })
+        .collect();
+    problems.sort();
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::DeletedObjectHasPayload,
+                "Paper.name".to_string(),
+                Some("https://example.org/paper/2".to_string())
+            ),
+            (
+                ProblemKind::DeletedObjectWithoutModified,
+                "Paper.modified".to_string(),
+                Some("https://example.org/paper/3".to_string())
+            ),
+            (
+                ProblemKind::LinkToDeletedObject("Paper"),
+                "Paper.relatedPaper".to_string(),
+                Some("https://example.org/paper/1".to_string())
+            ),
+        ]
+    );
+    assert_eq!(
+        reporter
+            .get_storage()
+            .deleted_counts()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [("Paper", 2)]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rule_selection() -> Result<()> {
+    // The linked person isn't in the cache, so loading it would panic
+    let mut paper = paper(1);
+    paper["originatorPerson"] = json!(["https://example.org/person/1"]);
+    let mut files = base_api();
+    files.push(("https://example.org/paper", list(vec![paper], None)));
+
+    let mut config = Config::default();
+    config.add_ignore("OPARL003")?;
+    config.add_ignore("ObjectNotInList")?;
+    config.add_ignore("RequiredFieldMissing")?;
+    let reporter = ReporterCli::new(Storage::with_config(config), true, None);
+    validate_oparl_api(
+        "https://example.org/system",
+        &reporter,
+        &OparlClient::new(MemoryCache::new(files)),
+        &ValidationOptions::default(),
+    )
+    .await?;
+
+    let problems = reporter.get_storage().problems.lock().clone();
+    assert!(!problems.is_empty());
+    assert!(problems
+        .iter()
+        .all(|problem| problem.kind.name() == "ListLinkMissing"));
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use oparl_validator_rs::external_list::ExternalList;
+use oparl_validator_rs::lang::Lang;
+use oparl_validator_rs::pagination::PaginationChecker;
+use oparl_validator_rs::visit::ProblemKind;
+use serde_json::{json, Value};
+
+fn page(
+    current_page: usize,
+    objects: usize,
+    self_: &str,
+    next: Option<&str>,
+) -> ExternalList<Value> {
+    serde_json::from_value(json!({
+        "data": vec![json!({}); objects],
+        "pagination": {
+            "totalElements": 5,
+            "elementsPerPage": 2,
+            "currentPage": current_page,
+            "totalPages": 3
+        },
+        "links": {
+            "first": "https://example.org/paper?page=1",
+            "self": self_,
+            "last": "https://example.org/paper?page=3",
+            "next": next
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_pagination_problems() {
+    let mut checker = PaginationChecker::new("https://example.org/paper", Lang::De);
+    let mut problems = Vec::new();
+    problems.extend(checker.check_page(
+        &page(
+            1,
+            2,
+            "https://example.org/paper?page=1",
+            Some("https://example.org/paper?page=2"),
+        ),
+        "https://example.org/paper",
+    ));
+    problems.extend(checker.check_page(
+        &page(
+            3,
+            1,
+            "https://example.org/paper?page=2",
+            Some("https://example.org/paper?page=3"),
+        ),
+        "https://example.org/paper?page=2",
+    ));
+    problems.extend(checker.check_page(
+        &page(3, 1, "https://example.org/paper?page=4", None),
+        "https://example.org/paper?page=3",
+    ));
+    problems.extend(checker.finish());
+
+    let kinds: Vec<ProblemKind> = problems.into_iter().map(|problem| problem.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            ProblemKind::PaginationCurrentPage,
+            ProblemKind::PaginationElementsPerPage,
+            ProblemKind::PaginationCurrentPage,
+            ProblemKind::ListLinkWrong("self"),
+            ProblemKind::PaginationTotalElements,
+        ]
+    );
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use oparl_validator_rs::cli::ReporterCli;
+use oparl_validator_rs::client::{Cache, FileCache, OparlClient, Revalidation};
+use oparl_validator_rs::report_revalidations;
+use oparl_validator_rs::reporter::Reporter;
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::ProblemKind;
+use serde_json::Value;
+use std::fs::File;
+use std::time::{Duration, UNIX_EPOCH};
+
+mod common;
+use common::Response;
+
+/// Answers `/etag` with 304 if the etag matches, `/ignored` always with the full response and
+/// `/plain...` without validators
+fn serve() -> String {
+    common::serve(|request| {
+        let etag = request.headers.get("if-none-match").map(String::as_str);
+        if request.line.contains(" /etag ") && etag == Some("\"1\"") {
+            Response {
+                headers: vec!["ETag: \"1\"".to_string()],
+                ..Response::status("304 Not Modified")
+            }
+        } else if request.line.contains(" /plain") {
+            Response {
+                body: "{}",
+                ..Response::status("200 OK")
+            }
+        } else {
+            Response {
+                headers: vec!["ETag: \"1\"".to_string()],
+                body: "{}",
+                ..Response::status("200 OK")
+            }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_revalidate() -> Result<()> {
+    let address = serve();
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().join("cache");
+    let urls = ["/etag", "/ignored", "/plain"].map(|path| format!("{}{}", address, path));
+
+    // Without --revalidate, nothing is recorded
+    let client = OparlClient::new(FileCache::new(dir.clone())?);
+    for url in &urls {
+        client.get::<Value>(url).await?.unwrap();
+    }
+    assert_eq!(client.take_revalidations(), vec![]);
+
+    let client = OparlClient::new(FileCache::new(dir.clone())?).with_revalidate(true);
+    for url in &urls {
+        client.get::<Value>(url).await?.unwrap();
+        // Each url is only revalidated once per run
+        client.get::<Value>(url).await?.unwrap();
+    }
+    assert_eq!(
+        client.take_revalidations(),
+        vec![
+            (urls[0].clone(), Revalidation::NotModified),
+            (urls[1].clone(), Revalidation::Ignored),
+            (urls[2].clone(), Revalidation::NoValidators),
+        ]
+    );
+
+    // Expired responses are revalidated instead of loaded again, and count as fresh after a 304
+    // without writing the unchanged body again
+    let entries = FileCache::new(dir.clone())?.entries();
+    let entry = entries.iter().find(|entry| entry.url == urls[0]).unwrap();
+    let body = dir.join(&entry.file);
+    File::options()
+        .write(true)
+        .open(&body)?
+        .set_modified(UNIX_EPOCH)?;
+    let client = OparlClient::new(FileCache::new(dir.clone())?.with_max_age(Some(Duration::ZERO)))
+        .with_revalidate(true);
+    client.get::<Value>(&urls[0]).await?.unwrap();
+    assert_eq!(
+        client.take_revalidations(),
+        vec![(urls[0].clone(), Revalidation::NotModified)]
+    );
+    let cache = FileCache::new(dir.clone())?.with_max_age(Some(Duration::from_secs(60)));
+    assert!(cache.get(&urls[0])?.is_some());
+    assert_eq!(body.metadata()?.modified()?, UNIX_EPOCH);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_revalidation_problems_by_host() -> Result<()> {
+    let address = serve();
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().join("cache");
+    let urls = ["/ignored", "/plain2", "/plain1"].map(|path| format!("{}{}", address, path));
+    let client = OparlClient::new(FileCache::new(dir.clone())?);
+    for url in &urls {
+        client.get::<Value>(url).await?.unwrap();
+    }
+
+    let client = OparlClient::new(FileCache::new(dir)?).with_revalidate(true);
+    for url in &urls {
+        client.get::<Value>(url).await?.unwrap();
+    }
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    report_revalidations(&client, &reporter);
+    let problems = reporter.get_storage().problems.lock().clone();
+    let problems: Vec<_> = problems
+        .into_iter()
+        .map(|problem| (problem.kind, problem.detail))
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::NoCacheValidators,
+                format!("2 Antworten von 127.0.0.1, z.B. {}", urls[2])
+            ),
+            (
+                ProblemKind::ConditionalRequestIgnored,
+                format!("1 Antworten von 127.0.0.1, z.B. {}", urls[0])
+            ),
+        ]
+    );
+    Ok(())
+}
@@ -1,60 +1,39 @@
 use anyhow::Result;
-use fs_err::File;
-use oparl_validator_rs::cli::{problem_report, write_detail_report, ReporterCli};
+use chrono::{DateTime, Utc};
+use oparl_validator_rs::archive::ArchiveCache;
+use oparl_validator_rs::cli::{
+    problem_report, write_detail_report, write_machine_report, ReportEntry, ReportFormat,
+    ReporterCli,
+};
 use oparl_validator_rs::client::{Cache, OparlClient};
+use oparl_validator_rs::config::Config;
+use oparl_validator_rs::incremental::{ObjectIndex, SINCE_OVERLAP};
+use oparl_validator_rs::lang::Lang;
 use oparl_validator_rs::reporter::Reporter;
 use oparl_validator_rs::storage::Storage;
-use oparl_validator_rs::validate_oparl_api;
-use std::collections::HashMap;
-use std::io::Read;
-use std::path::Path;
-use tar::Archive;
-use zstd::Decoder;
-
-/// Normal file cache, excepts that it errors when a file is missing
-struct TestCache {
-    files: HashMap<String, Vec<u8>>,
-    prefix: String,
-}
+use oparl_validator_rs::visit::Severity;
+use oparl_validator_rs::{validate_oparl_api, ValidationOptions};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
-fn normalize_archive_paths(
-    entries: Result<(String, Vec<u8>)>,
-) -> Option<Result<(String, Vec<u8>)>> {
-    entries
-        .map(|(path, content)| {
-            // missing ids is saved through set_inner
-            if path.ends_with("missing_ids.json") {
-                return Some((path, content));
-            }
-            let path = path.strip_suffix(".json")?.to_string();
-            Some((path, content))
-        })
-        .transpose()
-}
+/// The archive cache, except that it errors when a response is missing and must not be written
+struct TestCache(ArchiveCache);
 
 impl TestCache {
     pub fn new(cache_archive: &Path, prefix: String) -> Result<Self> {
         // gzip takes ~1s, while both uncompressed and zstd take 40ms, while zstd is smaller than gzip
-        let files = Archive::new(Decoder::new(File::open(cache_archive)?)?)
-            .entries()?
-            .filter_map(|e| e.ok())
-            .map(|mut entry| -> Result<(String, Vec<u8>)> {
-                // Read name and contents
-                let path = entry.path()?.to_string_lossy().to_string();
-                let mut content = Vec::new();
-                entry.read_to_end(&mut content)?;
-                Ok((path, content))
-            })
-            .filter_map(normalize_archive_paths)
-            .collect::<Result<_>>()?;
-        Ok(Self { files, prefix })
+        Ok(Self(
+            ArchiveCache::open(cache_archive)?.with_legacy_prefix(prefix),
+        ))
     }
 }
 
 impl Cache for TestCache {
     fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
-        match self.files.get(key) {
-            Some(data) => Ok(Some(data.clone())),
+        match self.0.get_inner(key)? {
+            Some(data) => Ok(Some(data)),
             None => panic!("No hit for cache key: {}", key),
         }
     }
@@ -64,7 +43,10 @@ impl Cache for TestCache {
     }
 
     fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
-        self.get_inner(url.strip_prefix(&self.prefix).unwrap())
+        match self.0.get(url)? {
+            Some(data) => Ok(Some(data)),
+            None => panic!("No hit for cache key: {}", url),
+        }
     }
 
     fn set(&self, url: &str, data: &[u8]) -> Result<()> {
@@ -73,29 +55,51 @@ impl Cache for TestCache {
 }
 
 const SUMMARY: &str = r"=== Validierungsreport: Zusammenfassung ===
-Das Objekt vom Typ Meeting wurden von einem anderen Objekt verlinkt, ist aber nicht abrufbar (12 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11/meeting verlinkt von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11 und 0 anderen
-Das Objekt von Typ Location wurde von einem anderen Objekt verlinkt, fehlt aber in den externen Listen (35 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/location/1-11 verlinkt von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/person/15 und 0 anderen
-Das zwingend vorgeschriebene Feld Body.legislativeTerm fehlt (1 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body
-Das zwingend vorgeschriebene Feld Meeting.type fehlt (21 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting/1082 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=11
-Das zwingend vorgeschriebene Feld Paper.type fehlt (18 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/paper/3419 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/paper?page=73
-Das Feld AgendaItem.number ist angegeben, hat aber keinen Inhalt (1 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/agendaitem/6373 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=7
+[OPARL003] Das Objekt vom Typ Meeting wurden von einem anderen Objekt verlinkt, ist aber nicht abrufbar (12 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11/meeting verlinkt von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11 und 0 anderen
+[OPARL004] Das Objekt von Typ Location wurde von einem anderen Objekt verlinkt, fehlt aber in den externen Listen (35 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/location/1-11 verlinkt von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/person/15 und 0 anderen
+[OPARL005] Das zwingend vorgeschriebene Feld Body.legislativeTerm fehlt (1 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body
+[OPARL005] Das zwingend vorgeschriebene Feld Meeting.type fehlt (21 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting/1082 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=11
+[OPARL005] Das zwingend vorgeschriebene Feld Paper.type fehlt (18 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/paper/3419 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/paper?page=73
+[OPARL006] Das Feld AgendaItem.number ist angegeben, hat aber keinen Inhalt (1 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/agendaitem/6373 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=7
+[OPARL014] Das Feld Membership.organization verlinkt ein Objekt, das im Feld Organization.membership nicht zurück verlinkt (254 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/membership/1085-1-17-130 verlinkt https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-17, aber https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-17 fehlt der Rückverweis in Organization.membership
+[OPARL035] Das Feld Membership.STERNBERG:sortierung ist eine herstellerspezifische Erweiterung (533 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/membership/1000-1-8-341 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/person?page=2
+[OPARL035] Das Feld Organization.STERNBERG:gruppierung ist eine herstellerspezifische Erweiterung (18 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization
+[OPARL035] Das Feld Organization.STERNBERG:sortierung ist eine herstellerspezifische Erweiterung (18 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization/1-11 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/organization
+[OPARL035] Das Feld System.STERNBERG:RIMVersion ist eine herstellerspezifische Erweiterung (1 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/system
+[OPARL037] Das Feld File.body ist nicht im Standard definiert und nicht als herstellerspezifische Erweiterung (z.B. ris:feld) gekennzeichnet (6947 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/file/1-10010 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/paper?page=24
+[OPARL037] Das Feld Meeting.body ist nicht im Standard definiert und nicht als herstellerspezifische Erweiterung (z.B. ris:feld) gekennzeichnet (346 Fälle). Beispiel: https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting/1052 innerhalb von https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=10
+Fehler: 52 Fälle, Warnung: 7583 Fälle, Hinweis: 570 Fälle
+=== Gelöschte Objekte ===
+Meeting: 21
+Paper: 18
 ";
 
 const LOOK_AT_REPORT: &str = "\nAlle einzelnen Fälle finden sich in report.txt\n";
 
-#[tokio::test]
-async fn test_huertgenwald() -> Result<()> {
-    let reporter = ReporterCli::new(Storage::new(), false, None);
-    let endpoint_url = "https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/system";
+const HUERTGENWALD: &str = "https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/system";
+
+fn huertgenwald_cache() -> Result<TestCache> {
+    TestCache::new(
+        Path::new("test-data/cache_huertgenwald.tar.zst"),
+        HUERTGENWALD.trim_end_matches("system").to_string(),
+    )
+}
+
+async fn validate_huertgenwald(config: Config) -> Result<ReporterCli> {
+    let reporter = ReporterCli::new(Storage::with_config(config), false, None);
     validate_oparl_api::<_, _>(
-        endpoint_url,
+        HUERTGENWALD,
         &reporter,
-        &OparlClient::new(TestCache::new(
-            Path::new("test-data/cache_huertgenwald.tar.zst"),
-            endpoint_url.trim_end_matches("system").to_string(),
-        )?),
+        &OparlClient::new(huertgenwald_cache()?),
+        &ValidationOptions::default(),
     )
     .await?;
+    Ok(reporter)
+}
+
+#[tokio::test]
+async fn test_huertgenwald() -> Result<()> {
+    let reporter = validate_huertgenwald(Config::default()).await?;
 
     let mut out: Vec<u8> = Vec::new();
     let mut report: Vec<u8> = Vec::new();
@@ -113,3 +117,238 @@ async fn test_huertgenwald() -> Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn test_huertgenwald_ndjson() -> Result<()> {
+    let reporter = validate_huertgenwald(Config::default()).await?;
+
+    let mut report: Vec<u8> = Vec::new();
+    write_machine_report(reporter.get_storage(), ReportFormat::Ndjson, &mut report)?;
+    let entries: Vec<ReportEntry> = String::from_utf8(report)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+
+    assert_eq!(entries.len(), reporter.get_storage().problems.lock().len());
+    let missing_meeting = entries
+        .iter()
+        .find(|entry| entry.kind == "ObjectMissing")
+        .unwrap();
+    assert_eq!(missing_meeting.type_name.as_deref(), Some("Meeting"));
+    assert_eq!(missing_meeting.severity, Severity::Error);
+    assert!(missing_meeting.id.as_ref().unwrap().ends_with("/meeting"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_huertgenwald_english() -> Result<()> {
+    let config = Config {
+        lang: Lang::En,
+        ..Config::default()
+    };
+    let reporter = validate_huertgenwald(config).await?;
+
+    let mut out: Vec<u8> = Vec::new();
+    let mut report: Vec<u8> = Vec::new();
+    problem_report(reporter.get_storage(), &mut out, &mut report, true)?;
+    let out = String::from_utf8(out)?;
+    assert!(out.starts_with("=== Validation report: Summary ===\n"));
+    assert!(out.contains(
+        "\n[OPARL005] The required field Meeting.type is missing (21 cases). Example: \
+        https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting/1082 within \
+        https://sdnetrim.kdvz-frechen.de/rim4220/webservice/oparl/v1.0/body/1/meeting?page=11\n"
+    ));
+    assert!(out.contains("\nError: 52 cases, Warning: 7583 cases, Info: 570 cases\n"));
+    assert!(out.ends_with("=== Deleted objects ===\nMeeting: 21\nPaper: 18\n"));
+    // Nothing is left in german
+    assert!(!out.contains("Fälle") && !out.contains("innerhalb") && !out.contains("verlinkt"));
+    assert_eq!(out, String::from_utf8(report)?);
+    Ok(())
+}
+
+/// Copies the checkpoint once it exists and the run is well underway, as if the validation had
+/// been interrupted at that point
+struct InterruptingCache {
+    inner: TestCache,
+    checkpoint: PathBuf,
+    interrupted: PathBuf,
+    requests: AtomicUsize,
+}
+
+impl Cache for InterruptingCache {
+    fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.inner.get_inner(key)
+    }
+
+    fn set_inner(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.inner.set_inner(key, data)
+    }
+
+    fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        let requests = self.requests.fetch_add(1, Ordering::SeqCst);
+        if requests > 100 && self.checkpoint.is_file() && !self.interrupted.is_file() {
+            fs_err::copy(&self.checkpoint, &self.interrupted)?;
+        }
+        self.inner.get(url)
+    }
+}
+
+#[tokio::test]
+async fn test_huertgenwald_resume() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let checkpoint = dir.path().join("checkpoint.json");
+    let interrupted = dir.path().join("interrupted.json");
+
+    let options = ValidationOptions {
+        checkpoint: Some(checkpoint.clone()),
+        checkpoint_interval: Duration::from_secs(1),
+        ..ValidationOptions::default()
+    };
+    let reporter = ReporterCli::new(Storage::new(), false, None);
+    let cache = InterruptingCache {
+        inner: huertgenwald_cache()?,
+        checkpoint: checkpoint.clone(),
+        interrupted: interrupted.clone(),
+        requests: AtomicUsize::new(0),
+    };
+    validate_oparl_api(HUERTGENWALD, &reporter, &OparlClient::new(cache), &options).await?;
+    // A finished validation has nothing to resume
+    assert!(!checkpoint.is_file());
+    assert!(interrupted.is_file());
+
+    fs_err::rename(&interrupted, &checkpoint)?;
+    let resumed = ReporterCli::new(Storage::new(), false, None);
+    let options = ValidationOptions {
+        resume: true,
+        ..options
+    };
+    validate_oparl_api(
+        HUERTGENWALD,
+        &resumed,
+        &OparlClient::new(huertgenwald_cache()?),
+        &options,
+    )
+    .await?;
+    assert!(!checkpoint.is_file());
+
+    // Nothing is missing or counted twice
+    assert_eq!(
+        resumed.get_storage().problems.lock().len(),
+        reporter.get_storage().problems.lock().len()
+    );
+    let mut out: Vec<u8> = Vec::new();
+    problem_report(resumed.get_storage(), &mut out, &mut Vec::new(), true)?;
+    assert_eq!(String::from_utf8(out)?, SUMMARY);
+    Ok(())
+}
+
+/// Answers the lists filtered with `modified_since` with the changed papers and no changes
+/// for the other lists
+struct ChangedCache {
+    inner: TestCache,
+    paper_list: String,
+    changed_papers: Vec<Value>,
+}
+
+impl Cache for ChangedCache {
+    fn get_inner(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.inner.get_inner(key)
+    }
+
+    fn set_inner(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.inner.set_inner(key, data)
+    }
+
+    fn get(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        if !url.contains("modified_since") {
+            return self.inner.get(url);
+        }
+        let data = if url.starts_with(&self.paper_list) {
+            self.changed_papers.clone()
+        } else {
+            Vec::new()
+        };
+        let page = json!({"data": data, "pagination": {}, "links": {}});
+        Ok(Some(serde_json::to_vec(&page)?))
+    }
+}
+
+#[tokio::test]
+async fn test_huertgenwald_incremental() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let index_path = dir.path().join("incremental.json");
+    let options = ValidationOptions {
+        incremental: true,
+        index: Some(index_path.clone()),
+        ..ValidationOptions::default()
+    };
+
+    // Without an index, the lists are loaded completely
+    let reporter = ReporterCli::new(Storage::new(), false, None);
+    validate_oparl_api(
+        HUERTGENWALD,
+        &reporter,
+        &OparlClient::new(huertgenwald_cache()?),
+        &options,
+    )
+    .await?;
+    let mut out: Vec<u8> = Vec::new();
+    problem_report(reporter.get_storage(), &mut out, &mut Vec::new(), true)?;
+    assert_eq!(String::from_utf8(out)?, SUMMARY);
+
+    let index = ObjectIndex::load(&index_path)?.unwrap();
+    // The next run also loads the objects changed shortly before this run
+    let since = DateTime::parse_from_rfc3339(&index.since)?;
+    let now = DateTime::<Utc>::from(SystemTime::now());
+    assert!(now.signed_duration_since(since).num_seconds() >= SINCE_OVERLAP.as_secs() as i64);
+    let paper_list = index
+        .lists
+        .keys()
+        .find(|url| url.contains("paper"))
+        .unwrap()
+        .clone();
+    let papers = &index.lists[&paper_list];
+    assert_eq!(
+        papers.len(),
+        reporter.get_storage().list_objects.lock()[&paper_list].len()
+    );
+
+    // One changed and one new paper
+    let mut changed = papers[0].object.clone();
+    changed["name"] = json!("Geänderte Drucksache");
+    let changed_id = papers[0].id().unwrap().to_string();
+    let new_id = format!("{}/neu", paper_list);
+    let new = json!({
+        "id": new_id,
+        "type": "https://schema.oparl.org/1.1/Paper",
+        "name": "Neue Drucksache",
+    });
+    let cache = ChangedCache {
+        inner: huertgenwald_cache()?,
+        paper_list: paper_list.clone(),
+        changed_papers: vec![changed, new],
+    };
+    let incremental = ReporterCli::new(Storage::new(), false, None);
+    validate_oparl_api(
+        HUERTGENWALD,
+        &incremental,
+        &OparlClient::new(cache),
+        &options,
+    )
+    .await?;
+
+    let storage = incremental.get_storage();
+    assert!(storage.known_ids.lock().contains(&new_id));
+    assert_eq!(
+        storage.list_objects.lock()[&paper_list].len(),
+        papers.len() + 1
+    );
+    let merged = ObjectIndex::load(&index_path)?.unwrap();
+    let merged_papers = &merged.lists[&paper_list];
+    assert_eq!(merged_papers.len(), papers.len() + 1);
+    assert_eq!(merged_papers[0].id(), Some(changed_id.as_str()));
+    assert_eq!(merged_papers[0].object["name"], "Geänderte Drucksache");
+    assert_eq!(merged_papers.last().unwrap().id(), Some(new_id.as_str()));
+    assert!(merged.since >= index.since);
+    Ok(())
+}
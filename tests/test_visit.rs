@@ -0,0 +1,139 @@
+use oparl_validator_rs::cli::ReporterCli;
+use oparl_validator_rs::reporter::Reporter;
+use oparl_validator_rs::schema::{Meeting, Paper};
+use oparl_validator_rs::storage::Storage;
+use oparl_validator_rs::visit::{OparlObject, ProblemKind, Visitable};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// Visits a single object and returns the kind and path of the problems found
+fn visit<T: DeserializeOwned + OparlObject>(object: Value) -> Vec<(ProblemKind, String)> {
+    let object: T = serde_json::from_value(object).unwrap();
+    let reporter = ReporterCli::new(Storage::new(), true, None);
+    object.visit(&reporter, "https://example.org/list", object.get_id(), "");
+    let mut problems: Vec<_> = reporter
+        .get_storage()
+        .problems
+        .lock()
+        .iter()
+        .map(|problem| (problem.kind.clone(), problem.path.clone()))
+        .collect();
+    problems.sort();
+    problems
+}
+
+#[test]
+fn test_dates() {
+    let problems = visit::<Meeting>(json!({
+        "id": "https://example.org/meeting/1",
+        "type": "https://schema.oparl.org/1.1/Meeting",
+        "start": "2023-01-31T18:00:00",
+        "end": "2023-01-31",
+        "created": "31.01.2023 18:00",
+        "modified": "2023-01-31T18:00:00.123Z",
+        "invitation": {
+            "id": "https://example.org/file/1",
+            "type": "https://schema.oparl.org/1.1/File",
+            "accessUrl": "https://example.org/file/1.pdf",
+            "date": "2023-01-31T00:00:00+01:00"
+        }
+    }));
+    assert_eq!(
+        problems,
+        [
+            (ProblemKind::InvalidDate, "File.date".to_string()),
+            (ProblemKind::InvalidDate, "Meeting.created".to_string()),
+            (
+                ProblemKind::DateTimeMissingTimezone,
+                "Meeting.start".to_string()
+            ),
+            (
+                ProblemKind::DateInsteadOfDateTime,
+                "Meeting.end".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_object_types() {
+    let problems = visit::<Paper>(json!({
+        "id": "https://example.org/meeting/1",
+        "type": "https://schema.oparl.org/1.1/Meeting",
+        "consultation": [
+            {
+                "id": "https://example.org/consultation/1",
+                "type": "https://schema.oparl.org/1.1/consultation"
+            },
+            {
+                "id": "https://example.org/consultation/2",
+                "type": "https://schema.oparl.org/1.0/Consultation"
+            }
+        ]
+    }));
+    assert_eq!(
+        problems,
+        [
+            (
+                ProblemKind::WrongObjectType("Consultation"),
+                "Consultation.type".to_string()
+            ),
+            (
+                ProblemKind::WrongObjectType("Paper"),
+                "Paper.type".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_other_fields() {
+    let problems = visit::<Paper>(json!({
+        "id": "https://example.org/paper/1",
+        "type": "https://schema.oparl.org/1.1/Paper",
+        "modfied": "2023-01-31T18:00:00+01:00",
+        "relatedpaper": [],
+        "ris:status": "offen",
+        "sitzung": "https://example.org/meeting/1"
+    }));
+    assert_eq!(
+        problems,
+        [
+            (ProblemKind::VendorField, "Paper.ris:status".to_string()),
+            (
+                ProblemKind::MisspelledField("modified"),
+                "Paper.modfied".to_string()
+            ),
+            (
+                ProblemKind::MisspelledField("relatedPaper"),
+                "Paper.relatedpaper".to_string()
+            ),
+            (ProblemKind::UnknownField, "Paper.sitzung".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_deserialize_static_names() {
+    let kinds: Vec<ProblemKind> = serde_json::from_value(json!([
+        {"ObjectMissing": "Paper"},
+        {"MisspelledField": "relatedPaper"},
+        {"MissingBacklink": "Person.membership"},
+        {"FilterRequestFailed": "modified_since"},
+        {"ListLinkWrong": "self"},
+    ]))
+    .unwrap();
+    assert_eq!(
+        kinds,
+        [
+            ProblemKind::ObjectMissing("Paper"),
+            ProblemKind::MisspelledField("relatedPaper"),
+            ProblemKind::MissingBacklink("Person.membership"),
+            ProblemKind::FilterRequestFailed("modified_since"),
+            ProblemKind::ListLinkWrong("self"),
+        ]
+    );
+    // A typo in a checkpoint is an error instead of a new name
+    let err = serde_json::from_value::<ProblemKind>(json!({"ObjectMissing": "Papr"})).unwrap_err();
+    assert!(err.to_string().contains("Papr"), "{}", err);
+}